@@ -12,3 +12,9 @@ export const COLLECTION_URI =
 export const COLLECTION_URI_PREFIX =
   'https://kcona.s3.ap-northeast-2.amazonaws.com/json'
 export const NFT_PRICE = 1000 // KRW
+export const MAX_MINTS_PER_WALLET = 0 // 0 = unlimited
+export const MAX_SUPPLY = 0 // 0 = unlimited
+export const SOULBOUND = false // set to true to freeze NFTs in the holder's wallet
+export const PROGRAMMABLE = false // pNFTs cannot be soulbound
+export const SELLER_FEE_BASIS_POINTS = 0 // no royalties
+export const GATE_MINTS_TO_ZK_ESCROW = false // set to true to reject direct spl-nft mints
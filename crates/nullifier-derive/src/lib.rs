@@ -0,0 +1,90 @@
+//! Canonical nullifier derivation: select fields from a proof context,
+//! concatenate them in order and hash with keccak256.
+//!
+//! Shared by on-chain programs and off-chain clients so derivations never drift.
+
+use solana_keccak_hasher::hashv;
+
+/// Fields hashed by default (payment sender and transaction time)
+pub const DEFAULT_NULLIFIER_FIELDS: [&str; 2] = ["senderNickname", "transactionDate"];
+
+/// Extract the raw string value of `field` from a proof context JSON
+/// (e.g. `"senderNickname":"nickname"` -> `nickname`). Escaped characters are
+/// returned as they appear in the context.
+pub fn extract_context_field<'a>(context: &'a str, field: &str) -> Option<&'a str> {
+    let pattern = format!("\"{}\":\"", field);
+    let start = context.find(&pattern)? + pattern.len();
+    let rest = &context[start..];
+
+    let mut escaped = false;
+    for (i, c) in rest.char_indices() {
+        match c {
+            '\\' if !escaped => escaped = true,
+            '"' if !escaped => return Some(&rest[..i]),
+            _ => escaped = false,
+        }
+    }
+
+    None
+}
+
+/// keccak256 over the concatenation of `values`
+pub fn hash_fields(values: &[&str]) -> [u8; 32] {
+    let bytes: Vec<&[u8]> = values.iter().map(|value| value.as_bytes()).collect();
+    hashv(&bytes).to_bytes()
+}
+
+/// Derive the 32-byte nullifier from the selected context fields, in order.
+/// Returns `None` if any field is missing from the context.
+pub fn derive_nullifier(context: &str, fields: &[&str]) -> Option<[u8; 32]> {
+    let values = fields
+        .iter()
+        .map(|field| extract_context_field(context, field))
+        .collect::<Option<Vec<&str>>>()?;
+
+    Some(hash_fields(&values))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONTEXT: &str = "{\"extractedParameters\":{\"documentTitle\":\"송금확인증\",\"receivingBankAccount\":\"59733704003503(KB국민은행)\",\"recipientName\":\"이영분(부동산임대)\",\"senderNickname\":\"609호이현민\",\"transactionAmount\":\"-8,750\",\"transactionDate\":\"2025-06-17 22:08:30\"},\"providerHash\":\"0xffb501528259e6d684e1c2153fbbacab453fe9c97c336dc4f8f48d70a0e2a13d\"}";
+
+    #[test]
+    fn extract_context_field_reads_values() {
+        assert_eq!(
+            extract_context_field(CONTEXT, "senderNickname"),
+            Some("609호이현민")
+        );
+        assert_eq!(
+            extract_context_field(CONTEXT, "transactionDate"),
+            Some("2025-06-17 22:08:30")
+        );
+        assert_eq!(extract_context_field(CONTEXT, "missing"), None);
+    }
+
+    #[test]
+    fn extract_context_field_skips_escaped_quotes() {
+        let context = "{\"memo\":\"say \\\"hi\\\"\",\"next\":\"x\"}";
+        assert_eq!(
+            extract_context_field(context, "memo"),
+            Some("say \\\"hi\\\"")
+        );
+    }
+
+    #[test]
+    fn derive_nullifier_hashes_concatenated_fields() {
+        let derived = derive_nullifier(CONTEXT, &DEFAULT_NULLIFIER_FIELDS).unwrap();
+        let expected = hashv(&["609호이현민2025-06-17 22:08:30".as_bytes()]).to_bytes();
+        assert_eq!(derived, expected);
+    }
+
+    #[test]
+    fn derive_nullifier_requires_every_field() {
+        assert_eq!(
+            derive_nullifier(CONTEXT, &["senderNickname", "missing"]),
+            None
+        );
+    }
+}
@@ -1,4 +1,11 @@
 use anchor_lang::prelude::*;
+use anchor_lang::system_program::{
+    allocate, assign, create_account, transfer, Allocate, Assign, CreateAccount, Transfer,
+};
+
+use nullifier_derive::hash_fields;
+
+pub mod merkle;
 
 #[cfg(feature = "devnet")]
 declare_id!("5djS2Qd4ob9vWUA5qJc9iPeWnjrJ2CDQctGpyzjFhsRz");
@@ -10,51 +17,1379 @@ declare_id!("BvHdh8mMnXq9EnhrVD6Q1i1eR4SavuHnxZFXCCoCAuoZ");
 pub mod nullifier_registry {
     use super::*;
 
-    /// Initialize the nullifier registry
-    pub fn initialize(ctx: Context<Initialize>) -> Result<()> {
+    /// Initialize a nullifier registry for the given namespace
+    /// Each integrated product uses its own namespace so nullifiers cannot collide across products
+    /// Registries are seeded by (authority, registry_id), where `registry_id` is the authority's
+    /// next sequential id, so one authority can run e.g. staging and production side by side
+    pub fn initialize(ctx: Context<Initialize>, namespace: Pubkey, registry_id: u64) -> Result<()> {
+        let authority_registries = &mut ctx.accounts.authority_registries;
+        authority_registries.authority = ctx.accounts.authority.key();
+        authority_registries.registry_count = registry_id + 1;
+
         let registry = &mut ctx.accounts.registry;
+        registry.creator = ctx.accounts.authority.key();
+        registry.registry_id = registry_id;
+        registry.namespace = namespace;
         registry.authority = ctx.accounts.authority.key();
         registry.nullifier_count = 0;
+        registry.nullifier_ttl = 0;
+        registry.rent_recipient = registry.authority;
+        registry.pending_authority = None;
+        registry.paused = false;
+        registry.max_marks_per_hour = 0;
+        registry.has_bloom_filter = false;
+        registry.compressed = false;
+        registry.hash_width = 0;
+
+        let stats = &mut ctx.accounts.stats;
+        stats.registry = registry.key();
+        stats.total_marked = 0;
+        stats.last_marked_slot = 0;
+        stats.current_day = 0;
+        stats.daily_count = 0;
 
         msg!("Nullifier registry initialized");
+        msg!("Registry id: {}", registry.registry_id);
+        msg!("Namespace: {}", registry.namespace);
         msg!("Authority: {}", registry.authority);
         Ok(())
     }
 
+    /// Derive the addresses of an authority's registries with ids in `[start, start + limit)`
+    /// Returned via return data; `limit` is capped at `MAX_LISTED_REGISTRIES`
+    pub fn list_registries(
+        ctx: Context<ListRegistries>,
+        start: u64,
+        limit: u8,
+    ) -> Result<Vec<Pubkey>> {
+        let authority_registries = &ctx.accounts.authority_registries;
+        let end = start
+            .saturating_add(u64::from(limit.min(MAX_LISTED_REGISTRIES)))
+            .min(authority_registries.registry_count);
+
+        Ok((start..end)
+            .map(|registry_id| registry_address(&authority_registries.authority, registry_id).0)
+            .collect())
+    }
+
     /// Mark a nullifier as used
     /// This prevents replay attacks by ensuring each proof can only be used once
-    pub fn mark_nullifier(ctx: Context<MarkNullifier>, nullifier_hash: [u8; 32]) -> Result<()> {
+    /// Only the registry authority, allowlisted signers, or allowlisted calling programs may mark
+    /// With `idempotent` set, an already-marked nullifier succeeds as a no-op instead of
+    /// failing with `NullifierAlreadyUsed` (for retry-safe relayers)
+    /// With `claim_timestamp_s` set, the registry TTL is counted from the claim's timestamp
+    /// rather than from now, so retention follows the attestation's validity window
+    pub fn mark_nullifier(
+        ctx: Context<MarkNullifier>,
+        nullifier_hash: NullifierHash,
+        claim_identifier_hash: [u8; 32],
+        idempotent: bool,
+        claim_timestamp_s: Option<u32>,
+    ) -> Result<()> {
+        require!(
+            !ctx.accounts.registry.paused,
+            NullifierError::RegistryPaused
+        );
+
+        let caller_program = calling_program(
+            ctx.accounts.caller_authority.as_ref(),
+            ctx.accounts.caller_program.as_ref(),
+        )?;
+        require!(
+            ctx.accounts.registry.is_authorized_caller(
+                caller_program,
+                ctx.accounts.user.key(),
+                ctx.accounts.caller_allowlist.as_deref(),
+            ),
+            NullifierError::UnauthorizedCaller
+        );
+
+        let hash_width = nullifier_hash.width();
+        ctx.accounts.registry.pin_hash_width(hash_width)?;
+        let nullifier_hash = nullifier_hash.to_stored();
+
+        let used_at = Clock::get()?.unix_timestamp;
+        let record_info = ctx.accounts.nullifier_record.to_account_info();
+        if let Some(existing) =
+            load_nullifier_record(&record_info, &nullifier_hash)?.filter(|r| r.is_live(used_at))
+        {
+            require!(
+                existing.reserved_until.is_none(),
+                NullifierError::NullifierCurrentlyReserved
+            );
+            require!(idempotent, NullifierError::NullifierAlreadyUsed);
+            msg!("Nullifier already marked, skipping (idempotent)");
+            return Ok(());
+        }
+
+        let expires_at = match claim_timestamp_s {
+            Some(claim_timestamp_s) => {
+                let claimed_at = i64::from(claim_timestamp_s);
+                require!(claimed_at <= used_at, NullifierError::InvalidClaimTimestamp);
+                let expires_at = ctx.accounts.registry.expiry_from(claimed_at);
+                // A record born expired could be closed and replayed immediately
+                require!(
+                    expires_at.map_or(true, |expires_at| expires_at > used_at),
+                    NullifierError::ClaimOutsideRetention
+                );
+                expires_at
+            }
+            None => ctx.accounts.registry.expiry_from(used_at),
+        };
+
+        apply_rate_limit(
+            ctx.accounts.rate_limit.as_deref_mut(),
+            &ctx.accounts.registry,
+            ctx.accounts.user.key(),
+            1,
+        )?;
+
+        create_nullifier_record(
+            &ctx.accounts.registry.key(),
+            &record_info,
+            &ctx.accounts.payer.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            &NullifierRecord {
+                version: NULLIFIER_RECORD_VERSION,
+                nullifier_hash,
+                used_at,
+                used_by: ctx.accounts.user.key(),
+                claim_identifier_hash,
+                marked_by_program: caller_program,
+                expires_at,
+                reserved_until: None,
+                hash_width,
+                sequence: ctx.accounts.registry.nullifier_count + 1,
+                reserved: [0u8; NULLIFIER_RECORD_RESERVED_BYTES],
+            },
+        )?;
+
+        let registry = &mut ctx.accounts.registry;
+        registry.nullifier_count += 1;
+
+        ctx.accounts.stats.record_marks(1, &Clock::get()?);
+
+        insert_into_bloom_filter(
+            registry,
+            ctx.accounts.bloom_filter.as_ref(),
+            &nullifier_hash,
+        )?;
+
+        emit!(NullifierMarked {
+            nullifier_hash,
+            user: ctx.accounts.user.key(),
+            namespace: registry.namespace,
+            marked_by_program: caller_program,
+            sequence: registry.nullifier_count,
+        });
+
+        Ok(())
+    }
+
+    /// Derive the nullifier on-chain from its canonical field values and mark it
+    /// The values are concatenated in order and hashed with keccak256 (see `nullifier-derive`),
+    /// so every caller gets the same derivation. Returns the derived nullifier hash
+    pub fn derive_and_mark(
+        ctx: Context<DeriveAndMark>,
+        fields: Vec<String>,
+        claim_identifier_hash: [u8; 32],
+        idempotent: bool,
+    ) -> Result<[u8; 32]> {
+        require!(
+            !ctx.accounts.registry.paused,
+            NullifierError::RegistryPaused
+        );
+        require!(!fields.is_empty(), NullifierError::InvalidNullifier);
+
+        let caller_program = calling_program(
+            ctx.accounts.caller_authority.as_ref(),
+            ctx.accounts.caller_program.as_ref(),
+        )?;
+        require!(
+            ctx.accounts.registry.is_authorized_caller(
+                caller_program,
+                ctx.accounts.user.key(),
+                ctx.accounts.caller_allowlist.as_deref(),
+            ),
+            NullifierError::UnauthorizedCaller
+        );
+        ctx.accounts.registry.pin_hash_width(32)?;
+
+        let values: Vec<&str> = fields.iter().map(String::as_str).collect();
+        let nullifier_hash = hash_fields(&values);
+
+        // The record PDA depends on the derived hash, so it is checked here rather than via seeds
+        let record_info = ctx.accounts.nullifier_record.to_account_info();
+        let (expected_record, _) =
+            nullifier_record_address(&ctx.accounts.registry.key(), &nullifier_hash);
+        require_keys_eq!(
+            record_info.key(),
+            expected_record,
+            NullifierError::InvalidNullifierAccount
+        );
+
+        let used_at = Clock::get()?.unix_timestamp;
+        if let Some(existing) =
+            load_nullifier_record(&record_info, &nullifier_hash)?.filter(|r| r.is_live(used_at))
+        {
+            require!(
+                existing.reserved_until.is_none(),
+                NullifierError::NullifierCurrentlyReserved
+            );
+            require!(idempotent, NullifierError::NullifierAlreadyUsed);
+            msg!("Nullifier already marked, skipping (idempotent)");
+            return Ok(nullifier_hash);
+        }
+
+        apply_rate_limit(
+            ctx.accounts.rate_limit.as_deref_mut(),
+            &ctx.accounts.registry,
+            ctx.accounts.user.key(),
+            1,
+        )?;
+
+        create_nullifier_record(
+            &ctx.accounts.registry.key(),
+            &record_info,
+            &ctx.accounts.payer.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            &NullifierRecord {
+                version: NULLIFIER_RECORD_VERSION,
+                nullifier_hash,
+                used_at,
+                used_by: ctx.accounts.user.key(),
+                claim_identifier_hash,
+                marked_by_program: caller_program,
+                expires_at: ctx.accounts.registry.expiry_from(used_at),
+                reserved_until: None,
+                hash_width: 32,
+                sequence: ctx.accounts.registry.nullifier_count + 1,
+                reserved: [0u8; NULLIFIER_RECORD_RESERVED_BYTES],
+            },
+        )?;
+
+        let registry = &mut ctx.accounts.registry;
+        registry.nullifier_count += 1;
+
+        ctx.accounts.stats.record_marks(1, &Clock::get()?);
+
+        insert_into_bloom_filter(
+            registry,
+            ctx.accounts.bloom_filter.as_ref(),
+            &nullifier_hash,
+        )?;
+
+        emit!(NullifierMarked {
+            nullifier_hash,
+            user: ctx.accounts.user.key(),
+            namespace: registry.namespace,
+            marked_by_program: caller_program,
+            sequence: registry.nullifier_count,
+        });
+
+        Ok(nullifier_hash)
+    }
+
+    /// Temporarily hold a nullifier for `ttl` seconds without consuming it
+    /// (e.g. verify in one transaction, transfer in the next). The record is created
+    /// in reserved state owned by `user`; if it is not finalized before the reservation
+    /// lapses, the nullifier becomes available again
+    pub fn reserve_nullifier(
+        ctx: Context<ReserveNullifier>,
+        nullifier_hash: [u8; 32],
+        ttl: i64,
+    ) -> Result<()> {
+        require!(
+            !ctx.accounts.registry.paused,
+            NullifierError::RegistryPaused
+        );
+        require!(
+            ttl > 0 && ttl <= MAX_RESERVATION_TTL,
+            NullifierError::InvalidReservationTtl
+        );
+
+        let caller_program = calling_program(
+            ctx.accounts.caller_authority.as_ref(),
+            ctx.accounts.caller_program.as_ref(),
+        )?;
+        require!(
+            ctx.accounts.registry.is_authorized_caller(
+                caller_program,
+                ctx.accounts.user.key(),
+                ctx.accounts.caller_allowlist.as_deref(),
+            ),
+            NullifierError::UnauthorizedCaller
+        );
+        require!(
+            ctx.accounts.registry.accepts_hash_width(32),
+            NullifierError::HashWidthMismatch
+        );
+
+        apply_rate_limit(
+            ctx.accounts.rate_limit.as_deref_mut(),
+            &ctx.accounts.registry,
+            ctx.accounts.user.key(),
+            1,
+        )?;
+
+        let now = Clock::get()?.unix_timestamp;
+        let reserved_until = now + ttl;
+        create_nullifier_record(
+            &ctx.accounts.registry.key(),
+            &ctx.accounts.nullifier_record.to_account_info(),
+            &ctx.accounts.payer.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            &NullifierRecord {
+                version: NULLIFIER_RECORD_VERSION,
+                nullifier_hash,
+                used_at: now,
+                used_by: ctx.accounts.user.key(),
+                claim_identifier_hash: [0u8; 32],
+                marked_by_program: caller_program,
+                expires_at: None,
+                reserved_until: Some(reserved_until),
+                hash_width: 32,
+                sequence: 0,
+                reserved: [0u8; NULLIFIER_RECORD_RESERVED_BYTES],
+            },
+        )?;
+
+        emit!(NullifierReserved {
+            nullifier_hash,
+            user: ctx.accounts.user.key(),
+            namespace: ctx.accounts.registry.namespace,
+            reserved_until,
+        });
+
+        Ok(())
+    }
+
+    /// Convert the caller's live reservation into a permanent nullifier record
+    pub fn finalize_reservation(
+        ctx: Context<FinalizeReservation>,
+        nullifier_hash: [u8; 32],
+        claim_identifier_hash: [u8; 32],
+    ) -> Result<()> {
+        require!(
+            !ctx.accounts.registry.paused,
+            NullifierError::RegistryPaused
+        );
+
+        let caller_program = calling_program(
+            ctx.accounts.caller_authority.as_ref(),
+            ctx.accounts.caller_program.as_ref(),
+        )?;
+        require!(
+            ctx.accounts.registry.is_authorized_caller(
+                caller_program,
+                ctx.accounts.user.key(),
+                ctx.accounts.caller_allowlist.as_deref(),
+            ),
+            NullifierError::UnauthorizedCaller
+        );
+        ctx.accounts.registry.pin_hash_width(32)?;
+
+        let now = Clock::get()?.unix_timestamp;
         let nullifier_record = &mut ctx.accounts.nullifier_record;
-        nullifier_record.nullifier_hash = nullifier_hash;
-        nullifier_record.used_at = Clock::get()?.unix_timestamp;
-        nullifier_record.used_by = ctx.accounts.user.key();
+        let reserved_until = nullifier_record
+            .reserved_until
+            .ok_or(NullifierError::NotReserved)?;
+        require!(now < reserved_until, NullifierError::ReservationExpired);
+
+        nullifier_record.used_at = now;
+        nullifier_record.claim_identifier_hash = claim_identifier_hash;
+        nullifier_record.marked_by_program = caller_program;
+        nullifier_record.expires_at = ctx.accounts.registry.expiry_from(now);
+        nullifier_record.reserved_until = None;
+        nullifier_record.sequence = ctx.accounts.registry.nullifier_count + 1;
+
+        let registry = &mut ctx.accounts.registry;
+        registry.nullifier_count += 1;
+
+        ctx.accounts.stats.record_marks(1, &Clock::get()?);
+
+        insert_into_bloom_filter(
+            registry,
+            ctx.accounts.bloom_filter.as_ref(),
+            &nullifier_hash,
+        )?;
+
+        emit!(NullifierMarked {
+            nullifier_hash,
+            user: ctx.accounts.user.key(),
+            namespace: registry.namespace,
+            marked_by_program: caller_program,
+            sequence: registry.nullifier_count,
+        });
+
+        Ok(())
+    }
+
+    /// Move a record written by the pre-registry program (seeds `[b"nullifier", hash]`,
+    /// unversioned layout) into this registry and close the legacy account
+    /// The registry authority decides which namespace legacy nullifiers belong to; the payer
+    /// funds the new record and receives the legacy account's rent
+    pub fn migrate_nullifier_record(
+        ctx: Context<MigrateNullifierRecord>,
+        nullifier_hash: [u8; 32],
+    ) -> Result<()> {
+        let legacy_info = ctx.accounts.legacy_record.to_account_info();
+        require!(
+            !legacy_info.data_is_empty(),
+            NullifierError::RecordAlreadyMigrated
+        );
+        require_keys_eq!(
+            *legacy_info.owner,
+            crate::ID,
+            NullifierError::InvalidNullifierAccount
+        );
+        let legacy = LegacyNullifierRecord::try_from_account_data(&legacy_info.try_borrow_data()?)?;
+        require!(
+            legacy.nullifier_hash == nullifier_hash,
+            NullifierError::NullifierHashMismatch
+        );
 
         let registry = &mut ctx.accounts.registry;
+        registry.pin_hash_width(32)?;
         registry.nullifier_count += 1;
 
-        msg!("Nullifier marked as used: {:?}", nullifier_hash);
-        msg!("Used by: {}", ctx.accounts.user.key());
-        msg!("Total nullifiers: {}", registry.nullifier_count);
+        create_nullifier_record(
+            &registry.key(),
+            &ctx.accounts.nullifier_record.to_account_info(),
+            &ctx.accounts.payer.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            &NullifierRecord {
+                version: NULLIFIER_RECORD_VERSION,
+                nullifier_hash,
+                used_at: legacy.used_at,
+                used_by: legacy.used_by,
+                claim_identifier_hash: [0u8; 32],
+                marked_by_program: Pubkey::default(),
+                expires_at: None,
+                reserved_until: None,
+                hash_width: 32,
+                sequence: registry.nullifier_count,
+                reserved: [0u8; NULLIFIER_RECORD_RESERVED_BYTES],
+            },
+        )?;
+
+        ctx.accounts.stats.record_marks(1, &Clock::get()?);
+
+        insert_into_bloom_filter(
+            registry,
+            ctx.accounts.bloom_filter.as_ref(),
+            &nullifier_hash,
+        )?;
+
+        emit!(NullifierMarked {
+            nullifier_hash,
+            user: legacy.used_by,
+            namespace: registry.namespace,
+            marked_by_program: Pubkey::default(),
+            sequence: registry.nullifier_count,
+        });
+
+        // Close the legacy account so the nullifier lives in exactly one place
+        let payer_info = ctx.accounts.payer.to_account_info();
+        **payer_info.try_borrow_mut_lamports()? += legacy_info.lamports();
+        **legacy_info.try_borrow_mut_lamports()? = 0;
+        legacy_info.assign(&System::id());
+        legacy_info.resize(0)?;
+
+        msg!(
+            "Legacy nullifier record migrated to version {}",
+            NULLIFIER_RECORD_VERSION
+        );
+        Ok(())
+    }
+
+    /// Allow a program to CPI into the marking instructions of this registry, or a signer
+    /// to call them directly (authority only)
+    /// Creates the registry's caller allowlist PDA on first use
+    pub fn add_allowed_caller(ctx: Context<AddAllowedCaller>, program_id: Pubkey) -> Result<()> {
+        let caller_allowlist = &mut ctx.accounts.caller_allowlist;
+        caller_allowlist.registry = ctx.accounts.registry.key();
+        require!(
+            !caller_allowlist.programs.contains(&program_id),
+            NullifierError::CallerAlreadyAllowed
+        );
+        require!(
+            caller_allowlist.programs.len() < MAX_ALLOWLISTED_PROGRAMS,
+            NullifierError::TooManyAllowedCallers
+        );
+        caller_allowlist.programs.push(program_id);
+
+        emit!(AllowedCallerAdded {
+            registry: ctx.accounts.registry.key(),
+            namespace: ctx.accounts.registry.namespace,
+            program_id,
+            authority: ctx.accounts.authority.key(),
+        });
+
+        Ok(())
+    }
+
+    /// Remove a program or signer from the registry's caller allowlist (authority only)
+    pub fn remove_allowed_caller(
+        ctx: Context<RemoveAllowedCaller>,
+        program_id: Pubkey,
+    ) -> Result<()> {
+        let programs = &mut ctx.accounts.caller_allowlist.programs;
+        let index = programs
+            .iter()
+            .position(|allowed| *allowed == program_id)
+            .ok_or(NullifierError::CallerNotAllowed)?;
+        programs.remove(index);
+
+        emit!(AllowedCallerRemoved {
+            registry: ctx.accounts.registry.key(),
+            namespace: ctx.accounts.registry.namespace,
+            program_id,
+            authority: ctx.accounts.authority.key(),
+        });
+
+        Ok(())
+    }
+
+    /// Pause or resume marking for incident response
+    /// While paused, mark_nullifier fails so downstream consumers halt automatically
+    pub fn set_paused(ctx: Context<UpdateRegistry>, paused: bool) -> Result<()> {
+        let registry = &mut ctx.accounts.registry;
+        registry.paused = paused;
+
+        msg!("Registry paused: {}", paused);
+        Ok(())
+    }
+
+    /// Cap how many nullifiers each user may mark (or reserve) per hour in this namespace
+    /// 0 disables the limit. While a limit is set, marking instructions must pass the
+    /// user's rate-limit account. The limit is per signing key, so it slows down a single
+    /// key but can be bypassed by spreading marks over fresh keys
+    pub fn set_rate_limit(ctx: Context<UpdateRegistry>, max_marks_per_hour: u32) -> Result<()> {
+        let registry = &mut ctx.accounts.registry;
+        registry.max_marks_per_hour = max_marks_per_hour;
+
+        msg!("Max marks per user per hour: {}", max_marks_per_hour);
+        Ok(())
+    }
+
+    /// Close the caller's rate-limit counter and reclaim its rent
+    /// Allowed once the registry has no limit or the counted hour has passed, so closing
+    /// cannot reset a window that is still being enforced
+    pub fn close_rate_limit(ctx: Context<CloseRateLimit>) -> Result<()> {
+        require!(
+            !ctx.accounts.rate_limit.window_active(
+                ctx.accounts.registry.max_marks_per_hour,
+                Clock::get()?.unix_timestamp
+            ),
+            NullifierError::RateLimitWindowActive
+        );
+
+        msg!("Rate limit closed for user: {}", ctx.accounts.user.key());
+        Ok(())
+    }
+
+    /// Step 1 of authority transfer: propose a new authority
+    pub fn propose_authority(ctx: Context<UpdateRegistry>, new_authority: Pubkey) -> Result<()> {
+        let registry = &mut ctx.accounts.registry;
+        registry.pending_authority = Some(new_authority);
+
+        msg!("Proposed new authority: {}", new_authority);
+        Ok(())
+    }
+
+    /// Step 2 of authority transfer: the proposed authority accepts
+    pub fn accept_authority(ctx: Context<AcceptAuthority>) -> Result<()> {
+        let registry = &mut ctx.accounts.registry;
+        let new_authority = ctx.accounts.new_authority.key();
+        require!(
+            registry.pending_authority == Some(new_authority),
+            NullifierError::NotPendingAuthority
+        );
+
+        let previous_authority = registry.authority;
+        registry.authority = new_authority;
+        registry.pending_authority = None;
+
+        emit!(AuthorityTransferred {
+            namespace: registry.namespace,
+            previous_authority,
+            new_authority,
+        });
+        Ok(())
+    }
+
+    /// Configure how long new nullifiers stay unique (0 = forever)
+    /// and where rent from closed expired records is returned
+    pub fn set_nullifier_ttl(
+        ctx: Context<UpdateRegistry>,
+        nullifier_ttl: i64,
+        rent_recipient: Pubkey,
+    ) -> Result<()> {
+        require!(nullifier_ttl >= 0, NullifierError::InvalidTtl);
+
+        let registry = &mut ctx.accounts.registry;
+        registry.nullifier_ttl = nullifier_ttl;
+        registry.rent_recipient = rent_recipient;
+
+        msg!("Nullifier TTL: {}s", registry.nullifier_ttl);
+        msg!("Rent recipient: {}", registry.rent_recipient);
+        Ok(())
+    }
+
+    /// Close an expired nullifier record, returning rent to the registry's rent recipient
+    /// Permissionless so anyone can clean up once the record has expired
+    pub fn close_expired_nullifier(
+        ctx: Context<CloseExpiredNullifier>,
+        nullifier_hash: NullifierHash,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            ctx.accounts.nullifier_record.is_expired(now),
+            NullifierError::NullifierNotExpired
+        );
+
+        msg!("Expired nullifier closed: {:?}", nullifier_hash.as_bytes());
+        Ok(())
+    }
+
+    /// Revoke a consumed nullifier so the user can retry (authority only)
+    /// Closes the record, returning rent to the rent recipient, and emits an audit event
+    pub fn revoke_nullifier(
+        ctx: Context<RevokeNullifier>,
+        nullifier_hash: NullifierHash,
+        reason_code: u8,
+    ) -> Result<()> {
+        require!(reason_code != 0, NullifierError::MissingReasonCode);
+
+        let record = &ctx.accounts.nullifier_record;
+        emit!(NullifierRevoked {
+            nullifier_hash: nullifier_hash.to_stored(),
+            namespace: ctx.accounts.registry.namespace,
+            authority: ctx.accounts.authority.key(),
+            used_by: record.used_by,
+            claim_identifier_hash: record.claim_identifier_hash,
+            reason_code,
+        });
+
+        Ok(())
+    }
+
+    /// Mark multiple nullifiers as used in one call
+    /// Record PDAs are passed as remaining_accounts in the same order as the entries
+    /// All-or-nothing: any already-used or mismatched record fails the whole batch
+    pub fn mark_nullifiers<'info>(
+        ctx: Context<'_, '_, '_, 'info, MarkNullifiers<'info>>,
+        entries: Vec<NullifierEntry>,
+    ) -> Result<()> {
+        require!(
+            !ctx.accounts.registry.paused,
+            NullifierError::RegistryPaused
+        );
+
+        let caller_program = calling_program(
+            ctx.accounts.caller_authority.as_ref(),
+            ctx.accounts.caller_program.as_ref(),
+        )?;
+        require!(
+            ctx.accounts.registry.is_authorized_caller(
+                caller_program,
+                ctx.accounts.user.key(),
+                ctx.accounts.caller_allowlist.as_deref(),
+            ),
+            NullifierError::UnauthorizedCaller
+        );
+        ctx.accounts.registry.pin_hash_width(32)?;
+        require!(
+            entries.len() == ctx.remaining_accounts.len(),
+            NullifierError::BatchLengthMismatch
+        );
+        apply_rate_limit(
+            ctx.accounts.rate_limit.as_deref_mut(),
+            &ctx.accounts.registry,
+            ctx.accounts.user.key(),
+            entries.len() as u32,
+        )?;
+
+        let registry_key = ctx.accounts.registry.key();
+        let namespace = ctx.accounts.registry.namespace;
+        let used_at = Clock::get()?.unix_timestamp;
+        let user = ctx.accounts.user.key();
+        let payer = ctx.accounts.payer.to_account_info();
+        let system_program = ctx.accounts.system_program.to_account_info();
+        let mut nullifier_count = ctx.accounts.registry.nullifier_count;
+
+        for (entry, record_info) in entries.iter().zip(ctx.remaining_accounts) {
+            nullifier_count += 1;
+            let record = NullifierRecord {
+                version: NULLIFIER_RECORD_VERSION,
+                nullifier_hash: entry.nullifier_hash,
+                used_at,
+                used_by: user,
+                claim_identifier_hash: entry.claim_identifier_hash,
+                marked_by_program: caller_program,
+                expires_at: ctx.accounts.registry.expiry_from(used_at),
+                reserved_until: None,
+                hash_width: 32,
+                sequence: nullifier_count,
+                reserved: [0u8; NULLIFIER_RECORD_RESERVED_BYTES],
+            };
+            create_nullifier_record(&registry_key, record_info, &payer, &system_program, &record)?;
+
+            insert_into_bloom_filter(
+                &ctx.accounts.registry,
+                ctx.accounts.bloom_filter.as_ref(),
+                &entry.nullifier_hash,
+            )?;
+
+            emit!(NullifierMarked {
+                nullifier_hash: entry.nullifier_hash,
+                user,
+                namespace,
+                marked_by_program: caller_program,
+                sequence: nullifier_count,
+            });
+        }
+
+        ctx.accounts.registry.nullifier_count = nullifier_count;
+        ctx.accounts
+            .stats
+            .record_marks(entries.len() as u64, &Clock::get()?);
+
+        Ok(())
+    }
+
+    /// Emit the consumed nullifiers among the given records so they can be carried over to
+    /// another registry (authority only). Record PDAs are passed as remaining_accounts;
+    /// reservations are skipped. Pair with `import_batch` on the destination registry
+    pub fn export_batch(ctx: Context<ExportBatch>) -> Result<()> {
+        let registry_key = ctx.accounts.registry.key();
+        let mut nullifiers = Vec::with_capacity(ctx.remaining_accounts.len());
+
+        for record_info in ctx.remaining_accounts {
+            require_keys_eq!(
+                *record_info.owner,
+                crate::ID,
+                NullifierError::InvalidNullifierAccount
+            );
+            let record =
+                NullifierRecord::try_deserialize(&mut &record_info.try_borrow_data()?[..])?;
+            let (expected_record, _) = record.nullifier().record_address(&registry_key);
+            require_keys_eq!(
+                record_info.key(),
+                expected_record,
+                NullifierError::InvalidNullifierAccount
+            );
+
+            if record.reserved_until.is_none() {
+                nullifiers.push(ExportedNullifier {
+                    nullifier: record.nullifier(),
+                    used_at: record.used_at,
+                    used_by: record.used_by,
+                    claim_identifier_hash: record.claim_identifier_hash,
+                    marked_by_program: record.marked_by_program,
+                });
+            }
+        }
+
+        emit!(NullifiersExported {
+            registry: registry_key,
+            namespace: ctx.accounts.registry.namespace,
+            nullifiers,
+        });
+
+        Ok(())
+    }
+
+    /// Recreate exported nullifiers in this registry (authority only)
+    /// Record PDAs are passed as remaining_accounts in the same order as the entries;
+    /// nullifiers already present are skipped so a partially applied batch can be retried
+    pub fn import_batch<'info>(
+        ctx: Context<'_, '_, '_, 'info, ImportBatch<'info>>,
+        entries: Vec<ExportedNullifier>,
+    ) -> Result<()> {
+        require!(
+            entries.len() == ctx.remaining_accounts.len(),
+            NullifierError::BatchLengthMismatch
+        );
+
+        let registry_key = ctx.accounts.registry.key();
+        let now = Clock::get()?.unix_timestamp;
+        let payer = ctx.accounts.authority.to_account_info();
+        let system_program = ctx.accounts.system_program.to_account_info();
+        let mut imported: u64 = 0;
+
+        for (entry, record_info) in entries.iter().zip(ctx.remaining_accounts) {
+            ctx.accounts
+                .registry
+                .pin_hash_width(entry.nullifier.width())?;
+            let nullifier_hash = entry.nullifier.to_stored();
+            let (expected_record, _) = entry.nullifier.record_address(&registry_key);
+            require_keys_eq!(
+                record_info.key(),
+                expected_record,
+                NullifierError::InvalidNullifierAccount
+            );
+            if load_nullifier_record(record_info, &nullifier_hash)?
+                .is_some_and(|record| record.is_live(now))
+            {
+                continue;
+            }
+
+            let record = NullifierRecord {
+                version: NULLIFIER_RECORD_VERSION,
+                nullifier_hash,
+                used_at: entry.used_at,
+                used_by: entry.used_by,
+                claim_identifier_hash: entry.claim_identifier_hash,
+                marked_by_program: entry.marked_by_program,
+                expires_at: ctx.accounts.registry.expiry_from(entry.used_at),
+                reserved_until: None,
+                hash_width: entry.nullifier.width(),
+                sequence: ctx.accounts.registry.nullifier_count + imported + 1,
+                reserved: [0u8; NULLIFIER_RECORD_RESERVED_BYTES],
+            };
+            create_nullifier_record(&registry_key, record_info, &payer, &system_program, &record)?;
+            insert_into_bloom_filter(
+                &ctx.accounts.registry,
+                ctx.accounts.bloom_filter.as_ref(),
+                &nullifier_hash,
+            )?;
+            imported += 1;
+
+            emit!(NullifierMarked {
+                nullifier_hash,
+                user: entry.used_by,
+                namespace: ctx.accounts.registry.namespace,
+                marked_by_program: entry.marked_by_program,
+                sequence: record.sequence,
+            });
+        }
+
+        ctx.accounts.registry.nullifier_count += imported;
+        ctx.accounts.stats.record_marks(imported, &Clock::get()?);
+
+        emit!(NullifiersImported {
+            registry: registry_key,
+            namespace: ctx.accounts.registry.namespace,
+            imported_count: imported,
+        });
+
+        Ok(())
+    }
+
+    /// Check multiple nullifiers in one call
+    /// Record PDAs are passed as remaining_accounts in the same order as the hashes
+    /// Returns error if any nullifier is already used
+    pub fn check_nullifiers(
+        ctx: Context<CheckNullifiers>,
+        nullifier_hashes: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        require!(
+            nullifier_hashes.len() == ctx.remaining_accounts.len(),
+            NullifierError::BatchLengthMismatch
+        );
+        require!(
+            ctx.accounts.registry.accepts_hash_width(32),
+            NullifierError::HashWidthMismatch
+        );
+
+        let registry_key = ctx.accounts.registry.key();
+        let now = Clock::get()?.unix_timestamp;
+        for (nullifier_hash, record_info) in nullifier_hashes.iter().zip(ctx.remaining_accounts) {
+            let (expected_record, _) = nullifier_record_address(&registry_key, nullifier_hash);
+            require_keys_eq!(
+                record_info.key(),
+                expected_record,
+                NullifierError::InvalidNullifierAccount
+            );
+
+            if load_nullifier_record(record_info, nullifier_hash)?
+                .is_some_and(|record| record.is_live(now))
+            {
+                emit!(NullifierCheckFailed {
+                    nullifier_hash: *nullifier_hash,
+                    namespace: ctx.accounts.registry.namespace,
+                });
+                return err!(NullifierError::NullifierAlreadyUsed);
+            }
+        }
 
         Ok(())
     }
 
     /// Check if a nullifier has been used (read-only)
     /// This is called via CPI from other programs to prevent replay attacks
-    /// Returns error if nullifier is already used
-    pub fn check_nullifier(ctx: Context<CheckNullifier>, nullifier_hash: [u8; 32]) -> Result<()> {
-        let nullifier_record_account = &ctx.accounts.nullifier_record;
+    /// Returns whether the nullifier is used via return data, so callers can branch on it
+    /// In strict mode, returns error if nullifier is already used
+    pub fn check_nullifier(
+        ctx: Context<CheckNullifier>,
+        nullifier_hash: NullifierHash,
+        strict: bool,
+    ) -> Result<bool> {
+        require!(
+            ctx.accounts
+                .registry
+                .accepts_hash_width(nullifier_hash.width()),
+            NullifierError::HashWidthMismatch
+        );
+        let nullifier_hash = nullifier_hash.to_stored();
 
-        // Check if the account is initialized (has data)
-        if !nullifier_record_account.data_is_empty() {
+        // Only a program-owned, well-formed record for this exact hash counts as used;
+        // live reservations count too, lapsed reservations and expired records do not
+        let now = Clock::get()?.unix_timestamp;
+        let is_used = load_nullifier_record(&ctx.accounts.nullifier_record, &nullifier_hash)?
+            .is_some_and(|record| record.is_live(now));
+        if is_used {
             // Account exists - it's been used
-            msg!("Nullifier already used: {:?}", nullifier_hash);
-            return err!(NullifierError::NullifierAlreadyUsed);
+            emit!(NullifierCheckFailed {
+                nullifier_hash,
+                namespace: ctx.accounts.registry.namespace,
+            });
+            if strict {
+                return err!(NullifierError::NullifierAlreadyUsed);
+            }
         }
 
-        msg!("Nullifier check passed: {:?} (not used before)", nullifier_hash);
+        Ok(is_used)
+    }
+
+    /// Read-only query returning the structured status of a nullifier via return data
+    pub fn get_nullifier(
+        ctx: Context<CheckNullifier>,
+        nullifier_hash: NullifierHash,
+    ) -> Result<NullifierStatus> {
+        require!(
+            ctx.accounts
+                .registry
+                .accepts_hash_width(nullifier_hash.width()),
+            NullifierError::HashWidthMismatch
+        );
+        let nullifier_hash = nullifier_hash.to_stored();
+        let namespace = ctx.accounts.registry.namespace;
+        let now = Clock::get()?.unix_timestamp;
+        let Some(record) = load_nullifier_record(&ctx.accounts.nullifier_record, &nullifier_hash)?
+            .filter(|record| record.is_live(now))
+        else {
+            return Ok(NullifierStatus {
+                nullifier_hash,
+                is_used: false,
+                used_at: 0,
+                used_by: Pubkey::default(),
+                namespace,
+            });
+        };
+
+        Ok(NullifierStatus {
+            nullifier_hash,
+            is_used: true,
+            used_at: record.used_at,
+            used_by: record.used_by,
+            namespace,
+        })
+    }
+
+    /// Switch this namespace to the compressed (indexed Merkle tree) nullifier set
+    /// An alternative to one PDA per nullifier for high-volume namespaces. Only allowed
+    /// before the first nullifier is marked; afterwards the PDA-based mark/check
+    /// instructions are rejected in favour of `append_nullifier`/`verify_non_inclusion`
+    pub fn initialize_compressed_set(ctx: Context<InitializeCompressedSet>) -> Result<()> {
+        require!(
+            ctx.accounts.registry.nullifier_count == 0,
+            NullifierError::CompressedSetAfterMarks
+        );
+        ctx.accounts.registry.compressed = true;
+
+        let compressed_set = &mut ctx.accounts.compressed_set;
+        compressed_set.registry = ctx.accounts.registry.key();
+        compressed_set.filled_subtrees = [merkle::EMPTY_LEAF; merkle::MERKLE_DEPTH];
+        compressed_set.root = merkle::append_leaf(
+            &mut compressed_set.filled_subtrees,
+            0,
+            &merkle::IndexedLeaf::SENTINEL.hash(),
+        );
+        compressed_set.leaf_count = 1;
+        compressed_set.updated_slot = Clock::get()?.slot;
+
+        msg!(
+            "Compressed nullifier set initialized for namespace: {}",
+            ctx.accounts.registry.namespace
+        );
+        Ok(())
+    }
+
+    /// Mark a nullifier as used in the compressed set
+    /// `proof` shows the nullifier's lower neighbour in the current tree; that leaf is
+    /// re-pointed at the new nullifier, which is appended at the next free index
+    pub fn append_nullifier(
+        ctx: Context<AppendNullifier>,
+        nullifier_hash: [u8; 32],
+        proof: NonInclusionProof,
+    ) -> Result<()> {
+        require!(
+            !ctx.accounts.registry.paused,
+            NullifierError::RegistryPaused
+        );
+
+        let caller_program = calling_program(
+            ctx.accounts.caller_authority.as_ref(),
+            ctx.accounts.caller_program.as_ref(),
+        )?;
+        require!(
+            ctx.accounts.registry.is_authorized_caller(
+                caller_program,
+                ctx.accounts.user.key(),
+                ctx.accounts.caller_allowlist.as_deref(),
+            ),
+            NullifierError::UnauthorizedCaller
+        );
+        ctx.accounts.registry.pin_hash_width(32)?;
+
+        let compressed_set = &mut ctx.accounts.compressed_set;
+        verify_low_leaf(compressed_set, &nullifier_hash, &proof)?;
+        require!(
+            compressed_set.leaf_count < merkle::MAX_MERKLE_LEAVES,
+            NullifierError::CompressedSetFull
+        );
+
+        let low_leaf = merkle::IndexedLeaf {
+            value: proof.low_leaf.value,
+            next_value: nullifier_hash,
+        };
+        merkle::update_leaf(
+            &mut compressed_set.filled_subtrees,
+            compressed_set.leaf_count,
+            proof.low_index,
+            &low_leaf.hash(),
+            &proof.low_proof,
+        );
+
+        let leaf_index = compressed_set.leaf_count;
+        let new_leaf = merkle::IndexedLeaf {
+            value: nullifier_hash,
+            next_value: proof.low_leaf.next_value,
+        };
+        compressed_set.root = merkle::append_leaf(
+            &mut compressed_set.filled_subtrees,
+            leaf_index,
+            &new_leaf.hash(),
+        );
+        compressed_set.leaf_count += 1;
+        compressed_set.updated_slot = Clock::get()?.slot;
+
+        let registry = &mut ctx.accounts.registry;
+        registry.nullifier_count += 1;
+
+        ctx.accounts.stats.record_marks(1, &Clock::get()?);
+
+        insert_into_bloom_filter(
+            registry,
+            ctx.accounts.bloom_filter.as_ref(),
+            &nullifier_hash,
+        )?;
+
+        emit!(CompressedNullifierAppended {
+            registry: registry.key(),
+            nullifier_hash,
+            leaf_index,
+            low_index: proof.low_index,
+            root: ctx.accounts.compressed_set.root,
+        });
+        emit!(NullifierMarked {
+            nullifier_hash,
+            user: ctx.accounts.user.key(),
+            namespace: registry.namespace,
+            marked_by_program: caller_program,
+            sequence: registry.nullifier_count,
+        });
+
+        Ok(())
+    }
+
+    /// Prove a nullifier is absent from the compressed set by showing the leaf
+    /// whose value and successor bracket it
+    pub fn verify_non_inclusion(
+        ctx: Context<VerifyNonInclusion>,
+        nullifier_hash: [u8; 32],
+        proof: NonInclusionProof,
+    ) -> Result<()> {
+        verify_low_leaf(&ctx.accounts.compressed_set, &nullifier_hash, &proof)?;
+
+        msg!("Nullifier not in compressed set: {:?}", nullifier_hash);
+        Ok(())
+    }
+
+    /// Create the optional bloom filter for this namespace
+    /// Only allowed before the first nullifier is marked, since existing records are not
+    /// backfilled. Once it exists, every marking instruction must pass and update it
+    pub fn initialize_bloom_filter(ctx: Context<InitializeBloomFilter>) -> Result<()> {
+        require!(
+            ctx.accounts.registry.nullifier_count == 0,
+            NullifierError::BloomFilterAfterMarks
+        );
+        ctx.accounts.registry.has_bloom_filter = true;
+
+        let mut bloom_filter = ctx.accounts.bloom_filter.load_init()?;
+        bloom_filter.registry = ctx.accounts.registry.key();
+
+        msg!(
+            "Bloom filter initialized for namespace: {}",
+            ctx.accounts.registry.namespace
+        );
         Ok(())
     }
+
+    /// Cheap membership pre-check against the namespace bloom filter
+    /// Returns `false` if the nullifier is definitely unused, `true` if it may be used
+    pub fn probe_bloom(ctx: Context<ProbeBloom>, nullifier_hash: [u8; 32]) -> Result<bool> {
+        let bloom_filter = ctx.accounts.bloom_filter.load()?;
+        Ok(bloom_filter.contains(&nullifier_hash))
+    }
+}
+
+/// Program attributed with a marking call: this program's id for direct calls, or the
+/// program that signed for its `[b"registry_caller"]` PDA. Only that program can produce
+/// the signature, so the attribution is the immediate CPI caller whatever wraps it.
+fn calling_program(
+    caller_authority: Option<&Signer>,
+    caller_program: Option<&UncheckedAccount>,
+) -> Result<Pubkey> {
+    match (caller_authority, caller_program) {
+        (None, None) => Ok(crate::ID),
+        (Some(caller_authority), Some(caller_program)) => {
+            let program_id = caller_program.key();
+            require_keys_eq!(
+                caller_authority.key(),
+                caller_authority_address(&program_id).0,
+                NullifierError::InvalidCallerAuthority
+            );
+            Ok(program_id)
+        }
+        _ => err!(NullifierError::InvalidCallerAuthority),
+    }
+}
+
+/// PDA a calling program signs with to identify itself to the registry
+pub fn caller_authority_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"registry_caller"], program_id)
+}
+
+/// Derive the record PDA for a nullifier within a registry
+/// Registry PDA for the `registry_id`-th registry created by `creator`
+pub fn registry_address(creator: &Pubkey, registry_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            b"nullifier_registry",
+            creator.as_ref(),
+            &registry_id.to_le_bytes(),
+        ],
+        &crate::ID,
+    )
+}
+
+/// Record PDA for a full-width (32-byte) nullifier
+pub fn nullifier_record_address(registry: &Pubkey, nullifier_hash: &[u8; 32]) -> (Pubkey, u8) {
+    NullifierHash::Hash32(*nullifier_hash).record_address(registry)
+}
+
+/// Load the nullifier record stored at `record_info`, if any
+/// An empty account means unused; anything else must be a record owned by this program
+/// (discriminator checked) whose stored hash matches, so funded or foreign accounts
+/// can't spoof "used" status
+fn load_nullifier_record(
+    record_info: &AccountInfo,
+    nullifier_hash: &[u8; 32],
+) -> Result<Option<NullifierRecord>> {
+    if record_info.data_is_empty() {
+        return Ok(None);
+    }
+
+    require_keys_eq!(
+        *record_info.owner,
+        crate::ID,
+        NullifierError::InvalidNullifierAccount
+    );
+    let record = NullifierRecord::try_deserialize(&mut &record_info.try_borrow_data()?[..])?;
+    require!(
+        record.nullifier_hash == *nullifier_hash,
+        NullifierError::NullifierHashMismatch
+    );
+
+    Ok(Some(record))
+}
+
+/// Create a nullifier record PDA passed as a raw account and write its data.
+/// Mirrors Anchor's `init` (including pre-funded accounts) for records that
+/// cannot be declared statically, e.g. batches passed as remaining_accounts.
+fn create_nullifier_record<'info>(
+    registry: &Pubkey,
+    record_info: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    record: &NullifierRecord,
+) -> Result<()> {
+    let nullifier = record.nullifier();
+    let (expected_record, bump) = nullifier.record_address(registry);
+    require_keys_eq!(
+        record_info.key(),
+        expected_record,
+        NullifierError::InvalidNullifierAccount
+    );
+    if !record_info.data_is_empty() {
+        // Only an expired record or lapsed reservation may be taken over, reusing its account
+        let now = Clock::get()?.unix_timestamp;
+        let lapsed = load_nullifier_record(record_info, &record.nullifier_hash)?
+            .is_some_and(|existing| !existing.is_live(now));
+        require!(lapsed, NullifierError::NullifierAlreadyUsed);
+        return write_nullifier_record(record_info, record);
+    }
+
+    let bump_seed = [bump];
+    let seeds: &[&[u8]] = &[
+        nullifier.seed_domain(),
+        registry.as_ref(),
+        nullifier.as_bytes(),
+        &bump_seed,
+    ];
+    let signer_seeds = &[seeds];
+
+    let space = 8 + NullifierRecord::INIT_SPACE;
+    let rent_lamports = Rent::get()?.minimum_balance(space);
+    let current_lamports = record_info.lamports();
+
+    if current_lamports == 0 {
+        create_account(
+            CpiContext::new_with_signer(
+                system_program.clone(),
+                CreateAccount {
+                    from: payer.clone(),
+                    to: record_info.clone(),
+                },
+                signer_seeds,
+            ),
+            rent_lamports,
+            space as u64,
+            &crate::ID,
+        )?;
+    } else {
+        // Account was pre-funded: top up rent, then allocate and assign
+        let top_up = rent_lamports.saturating_sub(current_lamports);
+        if top_up > 0 {
+            transfer(
+                CpiContext::new(
+                    system_program.clone(),
+                    Transfer {
+                        from: payer.clone(),
+                        to: record_info.clone(),
+                    },
+                ),
+                top_up,
+            )?;
+        }
+        allocate(
+            CpiContext::new_with_signer(
+                system_program.clone(),
+                Allocate {
+                    account_to_allocate: record_info.clone(),
+                },
+                signer_seeds,
+            ),
+            space as u64,
+        )?;
+        assign(
+            CpiContext::new_with_signer(
+                system_program.clone(),
+                Assign {
+                    account_to_assign: record_info.clone(),
+                },
+                signer_seeds,
+            ),
+            &crate::ID,
+        )?;
+    }
+
+    write_nullifier_record(record_info, record)
+}
+
+/// Check that `proof.low_leaf` is in the compressed set and brackets `nullifier_hash`,
+/// which proves the nullifier is not in the set
+fn verify_low_leaf(
+    compressed_set: &CompressedNullifierSet,
+    nullifier_hash: &[u8; 32],
+    proof: &NonInclusionProof,
+) -> Result<()> {
+    require!(
+        proof.low_leaf.value != *nullifier_hash,
+        NullifierError::NullifierAlreadyUsed
+    );
+    require!(
+        proof.low_leaf.brackets(nullifier_hash)
+            && merkle::verify_inclusion(
+                &compressed_set.root,
+                &proof.low_leaf.hash(),
+                proof.low_index,
+                &proof.low_proof,
+            ),
+        NullifierError::InvalidMerkleProof
+    );
+
+    Ok(())
+}
+
+/// Add a newly marked nullifier to the registry's bloom filter
+/// Once the registry has a filter it must be passed, so it never misses a marked nullifier
+fn insert_into_bloom_filter(
+    registry: &NullifierRegistry,
+    bloom_filter: Option<&AccountLoader<BloomFilter>>,
+    nullifier_hash: &[u8; 32],
+) -> Result<()> {
+    match bloom_filter {
+        Some(bloom_filter) => bloom_filter.load_mut()?.insert(nullifier_hash),
+        None => require!(
+            !registry.has_bloom_filter,
+            NullifierError::BloomFilterRequired
+        ),
+    }
+
+    Ok(())
+}
+
+/// Count `marks` against the user's hourly window, failing once the namespace limit is hit
+/// The counter account is only required while the registry has a limit
+fn apply_rate_limit(
+    rate_limit: Option<&mut UserRateLimit>,
+    registry: &Account<NullifierRegistry>,
+    user: Pubkey,
+    marks: u32,
+) -> Result<()> {
+    let Some(rate_limit) = rate_limit else {
+        require!(
+            registry.max_marks_per_hour == 0,
+            NullifierError::RateLimitAccountRequired
+        );
+        return Ok(());
+    };
+
+    rate_limit.registry = registry.key();
+    rate_limit.user = user;
+    require!(
+        rate_limit.record_marks(
+            marks,
+            registry.max_marks_per_hour,
+            Clock::get()?.unix_timestamp
+        ),
+        NullifierError::RateLimitExceeded
+    );
+
+    Ok(())
+}
+
+fn write_nullifier_record(record_info: &AccountInfo, record: &NullifierRecord) -> Result<()> {
+    let mut data = record_info.try_borrow_mut_data()?;
+    let mut writer: &mut [u8] = &mut data;
+    record.try_serialize(&mut writer)?;
+
+    Ok(())
 }
 
 // ============================================================================
@@ -62,16 +1397,37 @@ pub mod nullifier_registry {
 // ============================================================================
 
 #[derive(Accounts)]
+#[instruction(namespace: Pubkey, registry_id: u64)]
 pub struct Initialize<'info> {
+    /// Per-authority counter; `registry_id` must be the next unused id
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + AuthorityRegistries::INIT_SPACE,
+        seeds = [b"authority_registries", authority.key().as_ref()],
+        bump,
+        constraint = registry_id == authority_registries.registry_count @ NullifierError::InvalidRegistryId,
+    )]
+    pub authority_registries: Account<'info, AuthorityRegistries>,
+
     #[account(
         init,
         payer = authority,
         space = 8 + NullifierRegistry::INIT_SPACE,
-        seeds = [b"nullifier_registry"],
+        seeds = [b"nullifier_registry", authority.key().as_ref(), &registry_id.to_le_bytes()],
         bump,
     )]
     pub registry: Account<'info, NullifierRegistry>,
 
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + NamespaceStats::INIT_SPACE,
+        seeds = [b"stats", registry.key().as_ref()],
+        bump,
+    )]
+    pub stats: Account<'info, NamespaceStats>,
+
     #[account(mut)]
     pub authority: Signer<'info>,
 
@@ -79,59 +1435,1186 @@ pub struct Initialize<'info> {
 }
 
 #[derive(Accounts)]
-#[instruction(nullifier_hash: [u8; 32])]
+#[instruction(nullifier_hash: NullifierHash)]
 pub struct MarkNullifier<'info> {
     #[account(
         mut,
-        seeds = [b"nullifier_registry"],
+        seeds = [b"nullifier_registry", registry.creator.as_ref(), &registry.registry_id.to_le_bytes()],
         bump,
+        constraint = !registry.compressed @ NullifierError::CompressedRegistry,
     )]
     pub registry: Account<'info, NullifierRegistry>,
 
+    /// Caller allowlist for this registry; only needed for callers other than the authority
     #[account(
-        init,
-        payer = user,
-        space = 8 + NullifierRecord::INIT_SPACE,
-        seeds = [b"nullifier", nullifier_hash.as_ref()],
+        seeds = [b"caller_allowlist", registry.key().as_ref()],
         bump,
     )]
-    pub nullifier_record: Account<'info, NullifierRecord>,
+    pub caller_allowlist: Option<Account<'info, CallerAllowlist>>,
 
-    #[account(mut)]
-    pub user: Signer<'info>,
+    /// CHECK: Created in the handler so an existing record yields `NullifierAlreadyUsed`
+    /// (or a no-op in idempotent mode) rather than a system "already in use" error
+    #[account(
+        mut,
+        seeds = [nullifier_hash.seed_domain(), registry.key().as_ref(), nullifier_hash.as_bytes()],
+        bump,
+    )]
+    pub nullifier_record: UncheckedAccount<'info>,
 
-    pub system_program: Program<'info, System>,
-}
+    #[account(
+        mut,
+        seeds = [b"stats", registry.key().as_ref()],
+        bump,
+    )]
+    pub stats: Account<'info, NamespaceStats>,
 
-#[derive(Accounts)]
-#[instruction(nullifier_hash: [u8; 32])]
-pub struct CheckNullifier<'info> {
-    /// CHECK: This account may or may not exist. We manually check if it's initialized.
+    /// Per-user rolling counter for the namespace rate limit; only required (and created)
+    /// while the registry has a limit
     #[account(
-        seeds = [b"nullifier", nullifier_hash.as_ref()],
+        init_if_needed,
+        payer = payer,
+        space = 8 + UserRateLimit::INIT_SPACE,
+        seeds = [b"rate_limit", registry.key().as_ref(), user.key().as_ref()],
         bump,
     )]
-    pub nullifier_record: AccountInfo<'info>,
-}
+    pub rate_limit: Option<Account<'info, UserRateLimit>>,
 
-// ============================================================================
+    /// Namespace bloom filter; required once the registry has one
+    #[account(
+        mut,
+        seeds = [b"bloom", registry.key().as_ref()],
+        bump,
+    )]
+    pub bloom_filter: Option<AccountLoader<'info, BloomFilter>>,
+
+    /// Consumer attributed in the nullifier record
+    pub user: Signer<'info>,
+
+    /// Pays rent for the record (may be a relayer distinct from the user)
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// PDA `[b"registry_caller"]` of `caller_program`, signed by it through `invoke_signed`;
+    /// omitted for direct calls
+    pub caller_authority: Option<Signer<'info>>,
+
+    /// CHECK: Program attributed with the call; checked against `caller_authority`
+    pub caller_program: Option<UncheckedAccount<'info>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct DeriveAndMark<'info> {
+    #[account(
+        mut,
+        seeds = [b"nullifier_registry", registry.creator.as_ref(), &registry.registry_id.to_le_bytes()],
+        bump,
+        constraint = !registry.compressed @ NullifierError::CompressedRegistry,
+    )]
+    pub registry: Account<'info, NullifierRegistry>,
+
+    /// Caller allowlist for this registry; only needed for callers other than the authority
+    #[account(
+        seeds = [b"caller_allowlist", registry.key().as_ref()],
+        bump,
+    )]
+    pub caller_allowlist: Option<Account<'info, CallerAllowlist>>,
+
+    /// CHECK: Record PDA for the derived hash; address and contents are checked in the handler
+    #[account(mut)]
+    pub nullifier_record: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"stats", registry.key().as_ref()],
+        bump,
+    )]
+    pub stats: Account<'info, NamespaceStats>,
+
+    /// Per-user rolling counter for the namespace rate limit; only required (and created)
+    /// while the registry has a limit
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + UserRateLimit::INIT_SPACE,
+        seeds = [b"rate_limit", registry.key().as_ref(), user.key().as_ref()],
+        bump,
+    )]
+    pub rate_limit: Option<Account<'info, UserRateLimit>>,
+
+    /// Namespace bloom filter; required once the registry has one
+    #[account(
+        mut,
+        seeds = [b"bloom", registry.key().as_ref()],
+        bump,
+    )]
+    pub bloom_filter: Option<AccountLoader<'info, BloomFilter>>,
+
+    /// Consumer attributed in the nullifier record
+    pub user: Signer<'info>,
+
+    /// Pays rent for the record (may be a relayer distinct from the user)
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// PDA `[b"registry_caller"]` of `caller_program`, signed by it through `invoke_signed`;
+    /// omitted for direct calls
+    pub caller_authority: Option<Signer<'info>>,
+
+    /// CHECK: Program attributed with the call; checked against `caller_authority`
+    pub caller_program: Option<UncheckedAccount<'info>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(nullifier_hash: [u8; 32])]
+pub struct ReserveNullifier<'info> {
+    #[account(
+        seeds = [b"nullifier_registry", registry.creator.as_ref(), &registry.registry_id.to_le_bytes()],
+        bump,
+        constraint = !registry.compressed @ NullifierError::CompressedRegistry,
+    )]
+    pub registry: Account<'info, NullifierRegistry>,
+
+    /// Caller allowlist for this registry; only needed for callers other than the authority
+    #[account(
+        seeds = [b"caller_allowlist", registry.key().as_ref()],
+        bump,
+    )]
+    pub caller_allowlist: Option<Account<'info, CallerAllowlist>>,
+
+    /// CHECK: Created in the handler (or a lapsed reservation taken over)
+    #[account(
+        mut,
+        seeds = [b"nullifier", registry.key().as_ref(), nullifier_hash.as_ref()],
+        bump,
+    )]
+    pub nullifier_record: UncheckedAccount<'info>,
+
+    /// Per-user rolling counter for the namespace rate limit; only required (and created)
+    /// while the registry has a limit
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + UserRateLimit::INIT_SPACE,
+        seeds = [b"rate_limit", registry.key().as_ref(), user.key().as_ref()],
+        bump,
+    )]
+    pub rate_limit: Option<Account<'info, UserRateLimit>>,
+
+    /// Owner of the reservation, the only signer who may finalize it
+    pub user: Signer<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// PDA `[b"registry_caller"]` of `caller_program`, signed by it through `invoke_signed`;
+    /// omitted for direct calls
+    pub caller_authority: Option<Signer<'info>>,
+
+    /// CHECK: Program attributed with the call; checked against `caller_authority`
+    pub caller_program: Option<UncheckedAccount<'info>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(nullifier_hash: [u8; 32])]
+pub struct FinalizeReservation<'info> {
+    #[account(
+        mut,
+        seeds = [b"nullifier_registry", registry.creator.as_ref(), &registry.registry_id.to_le_bytes()],
+        bump,
+        constraint = !registry.compressed @ NullifierError::CompressedRegistry,
+    )]
+    pub registry: Account<'info, NullifierRegistry>,
+
+    /// Caller allowlist for this registry; only needed for callers other than the authority
+    #[account(
+        seeds = [b"caller_allowlist", registry.key().as_ref()],
+        bump,
+    )]
+    pub caller_allowlist: Option<Account<'info, CallerAllowlist>>,
+
+    #[account(
+        mut,
+        seeds = [b"nullifier", registry.key().as_ref(), nullifier_hash.as_ref()],
+        bump,
+        constraint = nullifier_record.used_by == user.key() @ NullifierError::ReservationNotOwned,
+    )]
+    pub nullifier_record: Account<'info, NullifierRecord>,
+
+    #[account(
+        mut,
+        seeds = [b"stats", registry.key().as_ref()],
+        bump,
+    )]
+    pub stats: Account<'info, NamespaceStats>,
+
+    /// Namespace bloom filter; required once the registry has one
+    #[account(
+        mut,
+        seeds = [b"bloom", registry.key().as_ref()],
+        bump,
+    )]
+    pub bloom_filter: Option<AccountLoader<'info, BloomFilter>>,
+
+    pub user: Signer<'info>,
+
+    /// PDA `[b"registry_caller"]` of `caller_program`, signed by it through `invoke_signed`;
+    /// omitted for direct calls
+    pub caller_authority: Option<Signer<'info>>,
+
+    /// CHECK: Program attributed with the call; checked against `caller_authority`
+    pub caller_program: Option<UncheckedAccount<'info>>,
+}
+
+#[derive(Accounts)]
+pub struct ListRegistries<'info> {
+    #[account(
+        seeds = [b"authority_registries", authority_registries.authority.as_ref()],
+        bump,
+    )]
+    pub authority_registries: Account<'info, AuthorityRegistries>,
+}
+
+#[derive(Accounts)]
+pub struct MarkNullifiers<'info> {
+    #[account(
+        mut,
+        seeds = [b"nullifier_registry", registry.creator.as_ref(), &registry.registry_id.to_le_bytes()],
+        bump,
+        constraint = !registry.compressed @ NullifierError::CompressedRegistry,
+    )]
+    pub registry: Account<'info, NullifierRegistry>,
+
+    /// Caller allowlist for this registry; only needed for callers other than the authority
+    #[account(
+        seeds = [b"caller_allowlist", registry.key().as_ref()],
+        bump,
+    )]
+    pub caller_allowlist: Option<Account<'info, CallerAllowlist>>,
+
+    #[account(
+        mut,
+        seeds = [b"stats", registry.key().as_ref()],
+        bump,
+    )]
+    pub stats: Account<'info, NamespaceStats>,
+
+    /// Per-user rolling counter for the namespace rate limit; only required (and created)
+    /// while the registry has a limit
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + UserRateLimit::INIT_SPACE,
+        seeds = [b"rate_limit", registry.key().as_ref(), user.key().as_ref()],
+        bump,
+    )]
+    pub rate_limit: Option<Account<'info, UserRateLimit>>,
+
+    /// Namespace bloom filter; required once the registry has one
+    #[account(
+        mut,
+        seeds = [b"bloom", registry.key().as_ref()],
+        bump,
+    )]
+    pub bloom_filter: Option<AccountLoader<'info, BloomFilter>>,
+
+    /// Consumer attributed in the nullifier record
+    pub user: Signer<'info>,
+
+    /// Pays rent for the record (may be a relayer distinct from the user)
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// PDA `[b"registry_caller"]` of `caller_program`, signed by it through `invoke_signed`;
+    /// omitted for direct calls
+    pub caller_authority: Option<Signer<'info>>,
+
+    /// CHECK: Program attributed with the call; checked against `caller_authority`
+    pub caller_program: Option<UncheckedAccount<'info>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CheckNullifiers<'info> {
+    #[account(
+        seeds = [b"nullifier_registry", registry.creator.as_ref(), &registry.registry_id.to_le_bytes()],
+        bump,
+        constraint = !registry.compressed @ NullifierError::CompressedRegistry,
+    )]
+    pub registry: Account<'info, NullifierRegistry>,
+}
+
+#[derive(Accounts)]
+#[instruction(nullifier_hash: NullifierHash)]
+pub struct CloseExpiredNullifier<'info> {
+    #[account(
+        seeds = [b"nullifier_registry", registry.creator.as_ref(), &registry.registry_id.to_le_bytes()],
+        bump,
+    )]
+    pub registry: Account<'info, NullifierRegistry>,
+
+    #[account(
+        mut,
+        seeds = [nullifier_hash.seed_domain(), registry.key().as_ref(), nullifier_hash.as_bytes()],
+        bump,
+        close = rent_recipient,
+    )]
+    pub nullifier_record: Account<'info, NullifierRecord>,
+
+    /// CHECK: Receives the reclaimed rent; validated against the registry config
+    #[account(mut, address = registry.rent_recipient)]
+    pub rent_recipient: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(nullifier_hash: [u8; 32])]
+pub struct MigrateNullifierRecord<'info> {
+    #[account(
+        mut,
+        seeds = [b"nullifier_registry", registry.creator.as_ref(), &registry.registry_id.to_le_bytes()],
+        bump,
+        has_one = authority,
+        constraint = !registry.compressed @ NullifierError::CompressedRegistry,
+    )]
+    pub registry: Account<'info, NullifierRegistry>,
+
+    /// CHECK: Record of the pre-registry program at its old address; owner, discriminator
+    /// and size are checked in the handler
+    #[account(
+        mut,
+        seeds = [b"nullifier", nullifier_hash.as_ref()],
+        bump,
+    )]
+    pub legacy_record: UncheckedAccount<'info>,
+
+    /// CHECK: Record PDA in this registry; created by the handler
+    #[account(
+        mut,
+        seeds = [b"nullifier", registry.key().as_ref(), nullifier_hash.as_ref()],
+        bump,
+    )]
+    pub nullifier_record: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"stats", registry.key().as_ref()],
+        bump,
+    )]
+    pub stats: Account<'info, NamespaceStats>,
+
+    /// Namespace bloom filter; required once the registry has one
+    #[account(
+        mut,
+        seeds = [b"bloom", registry.key().as_ref()],
+        bump,
+    )]
+    pub bloom_filter: Option<AccountLoader<'info, BloomFilter>>,
+
+    pub authority: Signer<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeBloomFilter<'info> {
+    #[account(
+        mut,
+        seeds = [b"nullifier_registry", registry.creator.as_ref(), &registry.registry_id.to_le_bytes()],
+        bump,
+        has_one = authority,
+    )]
+    pub registry: Account<'info, NullifierRegistry>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + std::mem::size_of::<BloomFilter>(),
+        seeds = [b"bloom", registry.key().as_ref()],
+        bump,
+    )]
+    pub bloom_filter: AccountLoader<'info, BloomFilter>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeCompressedSet<'info> {
+    #[account(
+        mut,
+        seeds = [b"nullifier_registry", registry.creator.as_ref(), &registry.registry_id.to_le_bytes()],
+        bump,
+        has_one = authority,
+    )]
+    pub registry: Account<'info, NullifierRegistry>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + CompressedNullifierSet::INIT_SPACE,
+        seeds = [b"compressed_set", registry.key().as_ref()],
+        bump,
+    )]
+    pub compressed_set: Account<'info, CompressedNullifierSet>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AppendNullifier<'info> {
+    #[account(
+        mut,
+        seeds = [b"nullifier_registry", registry.creator.as_ref(), &registry.registry_id.to_le_bytes()],
+        bump,
+    )]
+    pub registry: Account<'info, NullifierRegistry>,
+
+    /// Caller allowlist for this registry; only needed for callers other than the authority
+    #[account(
+        seeds = [b"caller_allowlist", registry.key().as_ref()],
+        bump,
+    )]
+    pub caller_allowlist: Option<Account<'info, CallerAllowlist>>,
+
+    #[account(
+        mut,
+        seeds = [b"compressed_set", registry.key().as_ref()],
+        bump,
+    )]
+    pub compressed_set: Account<'info, CompressedNullifierSet>,
+
+    #[account(
+        mut,
+        seeds = [b"stats", registry.key().as_ref()],
+        bump,
+    )]
+    pub stats: Account<'info, NamespaceStats>,
+
+    /// Namespace bloom filter; required once the registry has one
+    #[account(
+        mut,
+        seeds = [b"bloom", registry.key().as_ref()],
+        bump,
+    )]
+    pub bloom_filter: Option<AccountLoader<'info, BloomFilter>>,
+
+    /// Consumer attributed in the emitted event
+    pub user: Signer<'info>,
+
+    /// PDA `[b"registry_caller"]` of `caller_program`, signed by it through `invoke_signed`;
+    /// omitted for direct calls
+    pub caller_authority: Option<Signer<'info>>,
+
+    /// CHECK: Program attributed with the call; checked against `caller_authority`
+    pub caller_program: Option<UncheckedAccount<'info>>,
+}
+
+#[derive(Accounts)]
+pub struct VerifyNonInclusion<'info> {
+    #[account(
+        seeds = [b"nullifier_registry", registry.creator.as_ref(), &registry.registry_id.to_le_bytes()],
+        bump,
+    )]
+    pub registry: Account<'info, NullifierRegistry>,
+
+    #[account(
+        seeds = [b"compressed_set", registry.key().as_ref()],
+        bump,
+    )]
+    pub compressed_set: Account<'info, CompressedNullifierSet>,
+}
+
+#[derive(Accounts)]
+pub struct ProbeBloom<'info> {
+    #[account(
+        seeds = [b"nullifier_registry", registry.creator.as_ref(), &registry.registry_id.to_le_bytes()],
+        bump,
+    )]
+    pub registry: Account<'info, NullifierRegistry>,
+
+    #[account(
+        seeds = [b"bloom", registry.key().as_ref()],
+        bump,
+    )]
+    pub bloom_filter: AccountLoader<'info, BloomFilter>,
+}
+
+#[derive(Accounts)]
+#[instruction(nullifier_hash: NullifierHash)]
+pub struct RevokeNullifier<'info> {
+    #[account(
+        seeds = [b"nullifier_registry", registry.creator.as_ref(), &registry.registry_id.to_le_bytes()],
+        bump,
+        has_one = authority,
+    )]
+    pub registry: Account<'info, NullifierRegistry>,
+
+    #[account(
+        mut,
+        seeds = [nullifier_hash.seed_domain(), registry.key().as_ref(), nullifier_hash.as_bytes()],
+        bump,
+        close = rent_recipient,
+    )]
+    pub nullifier_record: Account<'info, NullifierRecord>,
+
+    /// CHECK: Receives the reclaimed rent; validated against the registry config
+    #[account(mut, address = registry.rent_recipient)]
+    pub rent_recipient: UncheckedAccount<'info>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptAuthority<'info> {
+    #[account(
+        mut,
+        seeds = [b"nullifier_registry", registry.creator.as_ref(), &registry.registry_id.to_le_bytes()],
+        bump,
+    )]
+    pub registry: Account<'info, NullifierRegistry>,
+
+    pub new_authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ExportBatch<'info> {
+    #[account(
+        seeds = [b"nullifier_registry", registry.creator.as_ref(), &registry.registry_id.to_le_bytes()],
+        bump,
+        has_one = authority,
+    )]
+    pub registry: Account<'info, NullifierRegistry>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ImportBatch<'info> {
+    #[account(
+        mut,
+        seeds = [b"nullifier_registry", registry.creator.as_ref(), &registry.registry_id.to_le_bytes()],
+        bump,
+        has_one = authority,
+        constraint = !registry.compressed @ NullifierError::CompressedRegistry,
+    )]
+    pub registry: Account<'info, NullifierRegistry>,
+
+    #[account(
+        mut,
+        seeds = [b"stats", registry.key().as_ref()],
+        bump,
+    )]
+    pub stats: Account<'info, NamespaceStats>,
+
+    /// Namespace bloom filter; required once the registry has one
+    #[account(
+        mut,
+        seeds = [b"bloom", registry.key().as_ref()],
+        bump,
+    )]
+    pub bloom_filter: Option<AccountLoader<'info, BloomFilter>>,
+
+    /// Also pays rent for the imported records
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AddAllowedCaller<'info> {
+    #[account(
+        seeds = [b"nullifier_registry", registry.creator.as_ref(), &registry.registry_id.to_le_bytes()],
+        bump,
+        has_one = authority,
+    )]
+    pub registry: Account<'info, NullifierRegistry>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + CallerAllowlist::INIT_SPACE,
+        seeds = [b"caller_allowlist", registry.key().as_ref()],
+        bump,
+    )]
+    pub caller_allowlist: Account<'info, CallerAllowlist>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveAllowedCaller<'info> {
+    #[account(
+        seeds = [b"nullifier_registry", registry.creator.as_ref(), &registry.registry_id.to_le_bytes()],
+        bump,
+        has_one = authority,
+    )]
+    pub registry: Account<'info, NullifierRegistry>,
+
+    #[account(
+        mut,
+        seeds = [b"caller_allowlist", registry.key().as_ref()],
+        bump,
+    )]
+    pub caller_allowlist: Account<'info, CallerAllowlist>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateRegistry<'info> {
+    #[account(
+        mut,
+        seeds = [b"nullifier_registry", registry.creator.as_ref(), &registry.registry_id.to_le_bytes()],
+        bump,
+        has_one = authority,
+    )]
+    pub registry: Account<'info, NullifierRegistry>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CloseRateLimit<'info> {
+    #[account(
+        seeds = [b"nullifier_registry", registry.creator.as_ref(), &registry.registry_id.to_le_bytes()],
+        bump,
+    )]
+    pub registry: Account<'info, NullifierRegistry>,
+
+    #[account(
+        mut,
+        seeds = [b"rate_limit", registry.key().as_ref(), user.key().as_ref()],
+        bump,
+        close = user,
+    )]
+    pub rate_limit: Account<'info, UserRateLimit>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(nullifier_hash: NullifierHash)]
+pub struct CheckNullifier<'info> {
+    #[account(
+        seeds = [b"nullifier_registry", registry.creator.as_ref(), &registry.registry_id.to_le_bytes()],
+        bump,
+        constraint = !registry.compressed @ NullifierError::CompressedRegistry,
+    )]
+    pub registry: Account<'info, NullifierRegistry>,
+
+    /// CHECK: This account may or may not exist. Owner, discriminator and stored hash
+    /// are validated in the handler before it is reported as used.
+    #[account(
+        seeds = [nullifier_hash.seed_domain(), registry.key().as_ref(), nullifier_hash.as_bytes()],
+        bump,
+    )]
+    pub nullifier_record: AccountInfo<'info>,
+}
+
+// ============================================================================
 // Data Structures
 // ============================================================================
 
-/// Global nullifier registry
+/// Maximum number of programs in a registry's caller allowlist PDA
+pub const MAX_ALLOWLISTED_PROGRAMS: usize = 16;
+
+/// Callers permitted to use the marking instructions of one registry besides its authority
+#[account]
+#[derive(InitSpace)]
+pub struct CallerAllowlist {
+    pub registry: Pubkey,
+    /// Program ids (for CPI callers) or signer keys (for direct callers)
+    #[max_len(MAX_ALLOWLISTED_PROGRAMS)]
+    pub programs: Vec<Pubkey>,
+}
+
+/// Maximum number of registry addresses returned by `list_registries`
+pub const MAX_LISTED_REGISTRIES: u8 = 16;
+
+/// Number of registries created by an authority (ids are 0..registry_count)
+#[account]
+#[derive(InitSpace)]
+pub struct AuthorityRegistries {
+    pub authority: Pubkey,
+    pub registry_count: u64,
+}
+
+/// Longest a nullifier may be reserved before it must be finalized (seconds)
+pub const MAX_RESERVATION_TTL: i64 = 60 * 60;
+
+/// Nullifier registry scoped to a namespace
 #[account]
 #[derive(InitSpace)]
 pub struct NullifierRegistry {
+    /// Authority that created the registry (PDA seed; unchanged by authority transfers)
+    pub creator: Pubkey,
+    /// Sequential id among the creator's registries (PDA seed)
+    pub registry_id: u64,
+    /// Namespace chosen at initialization, reported in events
+    pub namespace: Pubkey,
     pub authority: Pubkey,
     pub nullifier_count: u64,
+    /// Seconds a new nullifier stays unique (0 = forever)
+    pub nullifier_ttl: i64,
+    /// Receives rent from closed expired records
+    pub rent_recipient: Pubkey,
+    /// Proposed authority awaiting acceptance
+    pub pending_authority: Option<Pubkey>,
+    /// When set, marking is disabled
+    pub paused: bool,
+    /// Marks allowed per user per hour (0 = unlimited)
+    pub max_marks_per_hour: u32,
+    /// Set once the bloom filter exists; marking instructions must then update it
+    pub has_bloom_filter: bool,
+    /// Set once the compressed set exists; nullifiers then live in its Merkle tree
+    /// instead of record PDAs
+    pub compressed: bool,
+    /// Nullifier width (16 or 32 bytes) this registry accepts, pinned by the first mark
+    /// (0 = not yet pinned)
+    pub hash_width: u8,
+}
+
+impl NullifierRegistry {
+    /// Direct calls require the authority or a signer in the caller allowlist PDA;
+    /// CPI calls require the program that signed for its caller PDA to be in the
+    /// caller allowlist PDA
+    pub fn is_authorized_caller(
+        &self,
+        caller_program: Pubkey,
+        signer: Pubkey,
+        caller_allowlist: Option<&CallerAllowlist>,
+    ) -> bool {
+        let allowlisted = |caller: &Pubkey| {
+            caller_allowlist.is_some_and(|allowlist| allowlist.programs.contains(caller))
+        };
+        if caller_program == crate::ID {
+            signer == self.authority || allowlisted(&signer)
+        } else {
+            allowlisted(&caller_program)
+        }
+    }
+
+    /// Whether nullifiers of `width` bytes may be marked or checked in this registry
+    pub fn accepts_hash_width(&self, width: u8) -> bool {
+        self.hash_width == 0 || self.hash_width == width
+    }
+
+    /// Pin the registry to `width` on first use and reject the other width afterwards,
+    /// so one nullifier cannot be marked twice under its 16- and 32-byte forms
+    pub fn pin_hash_width(&mut self, width: u8) -> Result<()> {
+        require!(
+            self.accepts_hash_width(width),
+            NullifierError::HashWidthMismatch
+        );
+        self.hash_width = width;
+        Ok(())
+    }
+
+    /// Expiry for a nullifier marked at `used_at`, if the registry has a TTL
+    pub fn expiry_from(&self, used_at: i64) -> Option<i64> {
+        if self.nullifier_ttl > 0 {
+            Some(used_at.saturating_add(self.nullifier_ttl))
+        } else {
+            None
+        }
+    }
+}
+/// Per-namespace usage statistics
+#[account]
+#[derive(InitSpace)]
+pub struct NamespaceStats {
+    pub registry: Pubkey,
+    pub total_marked: u64,
+    pub last_marked_slot: u64,
+    /// Day index (unix_timestamp / 86400) that daily_count refers to
+    pub current_day: i64,
+    pub daily_count: u64,
+}
+
+impl NamespaceStats {
+    pub const SECONDS_PER_DAY: i64 = 86_400;
+
+    /// Account for `count` newly marked nullifiers, rolling the daily counter over at day boundaries
+    pub fn record_marks(&mut self, count: u64, clock: &Clock) {
+        let day = clock.unix_timestamp / Self::SECONDS_PER_DAY;
+        if day != self.current_day {
+            self.current_day = day;
+            self.daily_count = 0;
+        }
+
+        self.total_marked += count;
+        self.daily_count += count;
+        self.last_marked_slot = clock.slot;
+    }
+}
+
+/// Per-user mark counter within a namespace, used for rate limiting
+#[account]
+#[derive(InitSpace)]
+pub struct UserRateLimit {
+    pub registry: Pubkey,
+    pub user: Pubkey,
+    /// Hour index (unix_timestamp / 3600) that hourly_count refers to
+    pub current_hour: i64,
+    pub hourly_count: u32,
+}
+
+impl UserRateLimit {
+    pub const SECONDS_PER_HOUR: i64 = 3_600;
+
+    /// Account for `count` marks at `now`, rolling the counter over at hour boundaries
+    /// Returns false (without counting) if this would exceed `max_per_hour` (0 = unlimited)
+    pub fn record_marks(&mut self, count: u32, max_per_hour: u32, now: i64) -> bool {
+        let hour = now / Self::SECONDS_PER_HOUR;
+        if hour != self.current_hour {
+            self.current_hour = hour;
+            self.hourly_count = 0;
+        }
+
+        let hourly_count = self.hourly_count.saturating_add(count);
+        if max_per_hour != 0 && hourly_count > max_per_hour {
+            return false;
+        }
+
+        self.hourly_count = hourly_count;
+        true
+    }
+
+    /// Whether marks are still being counted against `max_per_hour` at `now`
+    pub fn window_active(&self, max_per_hour: u32, now: i64) -> bool {
+        max_per_hour != 0
+            && self.hourly_count > 0
+            && self.current_hour == now / Self::SECONDS_PER_HOUR
+    }
+}
+
+/// Size of the bloom filter bit array in bytes
+pub const BLOOM_FILTER_BYTES: usize = 1024;
+
+/// Number of bit positions set per nullifier
+pub const BLOOM_FILTER_HASHES: usize = 3;
+
+/// Fixed-size bloom filter of marked nullifiers for cheap "definitely unused" pre-checks
+#[account(zero_copy)]
+pub struct BloomFilter {
+    pub registry: Pubkey,
+    pub bits: [u8; BLOOM_FILTER_BYTES],
+}
+
+impl BloomFilter {
+    /// Nullifier hashes are already keccak outputs, so disjoint 4-byte slices
+    /// serve as independent hash functions
+    fn bit_positions(nullifier_hash: &[u8; 32]) -> [usize; BLOOM_FILTER_HASHES] {
+        let mut positions = [0usize; BLOOM_FILTER_HASHES];
+        for (i, position) in positions.iter_mut().enumerate() {
+            let mut chunk = [0u8; 4];
+            chunk.copy_from_slice(&nullifier_hash[i * 4..i * 4 + 4]);
+            *position = u32::from_le_bytes(chunk) as usize % (BLOOM_FILTER_BYTES * 8);
+        }
+        positions
+    }
+
+    pub fn insert(&mut self, nullifier_hash: &[u8; 32]) {
+        for position in Self::bit_positions(nullifier_hash) {
+            self.bits[position / 8] |= 1 << (position % 8);
+        }
+    }
+
+    pub fn contains(&self, nullifier_hash: &[u8; 32]) -> bool {
+        Self::bit_positions(nullifier_hash)
+            .iter()
+            .all(|position| self.bits[position / 8] & (1 << (position % 8)) != 0)
+    }
+}
+
+/// Indexed Merkle tree over a namespace's nullifiers (compressed mode)
+#[account]
+#[derive(InitSpace)]
+pub struct CompressedNullifierSet {
+    pub registry: Pubkey,
+    pub root: [u8; 32],
+    /// Leaves in the tree, including the zero sentinel at index 0
+    pub leaf_count: u64,
+    pub updated_slot: u64,
+    /// Completed left subtrees on the path to the next free leaf, so appends need no proof
+    pub filled_subtrees: [[u8; 32]; merkle::MERKLE_DEPTH],
+}
+
+/// Leaf at `low_index` whose value and successor bracket the nullifier
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct NonInclusionProof {
+    pub low_leaf: merkle::IndexedLeaf,
+    pub low_index: u64,
+    pub low_proof: Vec<[u8; 32]>,
+}
+
+/// Status returned by get_nullifier
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct NullifierStatus {
+    pub nullifier_hash: [u8; 32],
+    pub is_used: bool,
+    pub used_at: i64,
+    pub used_by: Pubkey,
+    pub namespace: Pubkey,
+}
+
+/// Nullifier as supplied by integrators: truncated 16-byte keccak (legacy token-escrow)
+/// or full-width 32-byte keccak. Each width has its own record PDA seed domain
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NullifierHash {
+    Hash16([u8; 16]),
+    Hash32([u8; 32]),
+}
+
+impl NullifierHash {
+    pub fn seed_domain(&self) -> &'static [u8] {
+        match self {
+            NullifierHash::Hash16(_) => b"nullifier16",
+            NullifierHash::Hash32(_) => b"nullifier",
+        }
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        match self {
+            NullifierHash::Hash16(hash) => hash,
+            NullifierHash::Hash32(hash) => hash,
+        }
+    }
+
+    pub fn width(&self) -> u8 {
+        self.as_bytes().len() as u8
+    }
+
+    /// Form stored in records and events: 16-byte hashes are zero-padded
+    pub fn to_stored(&self) -> [u8; 32] {
+        let mut stored = [0u8; 32];
+        stored[..self.as_bytes().len()].copy_from_slice(self.as_bytes());
+        stored
+    }
+
+    pub fn record_address(&self, registry: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[self.seed_domain(), registry.as_ref(), self.as_bytes()],
+            &crate::ID,
+        )
+    }
+}
+
+/// Nullifier to mark in a batch, with the claim that consumed it
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct NullifierEntry {
+    pub nullifier_hash: [u8; 32],
+    pub claim_identifier_hash: [u8; 32],
+}
+
+/// Compact consumed-nullifier entry carried between registries by export/import
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct ExportedNullifier {
+    pub nullifier: NullifierHash,
+    pub used_at: i64,
+    pub used_by: Pubkey,
+    pub claim_identifier_hash: [u8; 32],
+    /// Program that originally marked the nullifier, kept across registries
+    pub marked_by_program: Pubkey,
 }
+
 /// Individual nullifier record
 #[account]
 #[derive(InitSpace)]
 pub struct NullifierRecord {
+    /// Layout version (see `NULLIFIER_RECORD_VERSION`)
+    pub version: u8,
     pub nullifier_hash: [u8; 32], // Raw keccak256 hash bytes
     pub used_at: i64,
     pub used_by: Pubkey,
+    /// keccak256 of the claim identifier that consumed this nullifier
+    pub claim_identifier_hash: [u8; 32],
+    /// Program that marked this nullifier through its caller PDA (this program for direct calls)
+    pub marked_by_program: Pubkey,
+    /// After this time the record may be closed and the nullifier reused (None = never)
+    pub expires_at: Option<i64>,
+    /// Set while the nullifier is only reserved by `used_by`; cleared on finalize
+    pub reserved_until: Option<i64>,
+    /// Width of the nullifier in bytes (16-byte hashes are stored zero-padded)
+    pub hash_width: u8,
+    /// Position in the registry's consumption order (1-based; 0 while reserved)
+    pub sequence: u64,
+    /// Zeroed space for future fields, so they can be added without resizing records
+    pub reserved: [u8; NULLIFIER_RECORD_RESERVED_BYTES],
+}
+
+/// Current `NullifierRecord` layout version
+pub const NULLIFIER_RECORD_VERSION: u8 = 1;
+
+/// Bytes kept free at the end of every record for future fields
+pub const NULLIFIER_RECORD_RESERVED_BYTES: usize = 55;
+
+/// Unversioned record layout written before `version` existed (same discriminator)
+#[derive(AnchorDeserialize)]
+pub struct LegacyNullifierRecord {
+    pub nullifier_hash: [u8; 32],
+    pub used_at: i64,
+    pub used_by: Pubkey,
+}
+
+impl LegacyNullifierRecord {
+    pub const SPACE: usize = 8 + 32 + 8 + 32;
+
+    /// Parse a full legacy account (discriminator included)
+    pub fn try_from_account_data(data: &[u8]) -> Result<Self> {
+        require!(
+            data.len() == Self::SPACE && data[..8] == *NullifierRecord::DISCRIMINATOR,
+            NullifierError::InvalidNullifierAccount
+        );
+        Ok(Self::deserialize(&mut &data[8..])?)
+    }
+}
+
+impl NullifierRecord {
+    /// The nullifier this record was created for, in its original width
+    pub fn nullifier(&self) -> NullifierHash {
+        if self.hash_width == 16 {
+            let mut hash = [0u8; 16];
+            hash.copy_from_slice(&self.nullifier_hash[..16]);
+            NullifierHash::Hash16(hash)
+        } else {
+            NullifierHash::Hash32(self.nullifier_hash)
+        }
+    }
+
+    /// Whether the nullifier is unavailable: consumed, or reserved and not yet lapsed
+    pub fn is_held(&self, now: i64) -> bool {
+        match self.reserved_until {
+            Some(reserved_until) => now < reserved_until,
+            None => true,
+        }
+    }
+
+    /// Expired records and lapsed reservations may be closed
+    pub fn is_expired(&self, now: i64) -> bool {
+        !self.is_held(now) || matches!(self.expires_at, Some(expires_at) if now >= expires_at)
+    }
+
+    /// Whether the record still blocks its nullifier; expired records and lapsed
+    /// reservations free it even before they are closed
+    pub fn is_live(&self, now: i64) -> bool {
+        !self.is_expired(now)
+    }
+}
+
+// ============================================================================
+// Events
+// ============================================================================
+
+/// Emitted for every nullifier marked as used, including migrated and imported ones
+#[event]
+pub struct NullifierMarked {
+    pub nullifier_hash: [u8; 32],
+    pub user: Pubkey,
+    pub namespace: Pubkey,
+    pub marked_by_program: Pubkey,
+    /// Sequence number stored in the record (registry count after this mark)
+    pub sequence: u64,
+}
+
+/// Emitted for every nullifier appended to a compressed set, so indexers can rebuild the tree
+#[event]
+pub struct CompressedNullifierAppended {
+    pub registry: Pubkey,
+    pub nullifier_hash: [u8; 32],
+    /// Index the new leaf was appended at
+    pub leaf_index: u64,
+    /// Index of the lower neighbour re-pointed at the new leaf
+    pub low_index: u64,
+    pub root: [u8; 32],
+}
+
+/// Emitted when a nullifier is temporarily reserved
+#[event]
+pub struct NullifierReserved {
+    pub nullifier_hash: [u8; 32],
+    pub user: Pubkey,
+    pub namespace: Pubkey,
+    pub reserved_until: i64,
+}
+
+/// Snapshot of consumed nullifiers emitted by `export_batch`
+#[event]
+pub struct NullifiersExported {
+    pub registry: Pubkey,
+    pub namespace: Pubkey,
+    pub nullifiers: Vec<ExportedNullifier>,
+}
+
+/// Emitted after `import_batch` recreates records
+#[event]
+pub struct NullifiersImported {
+    pub registry: Pubkey,
+    pub namespace: Pubkey,
+    pub imported_count: u64,
+}
+
+/// Emitted when a check finds an already-used nullifier (possible replay attempt)
+#[event]
+pub struct NullifierCheckFailed {
+    pub nullifier_hash: [u8; 32],
+    pub namespace: Pubkey,
+}
+
+/// Emitted when a program or signer is added to a registry's caller allowlist
+#[event]
+pub struct AllowedCallerAdded {
+    pub registry: Pubkey,
+    pub namespace: Pubkey,
+    pub program_id: Pubkey,
+    pub authority: Pubkey,
+}
+
+/// Emitted when a program or signer is removed from a registry's caller allowlist
+#[event]
+pub struct AllowedCallerRemoved {
+    pub registry: Pubkey,
+    pub namespace: Pubkey,
+    pub program_id: Pubkey,
+    pub authority: Pubkey,
+}
+
+/// Emitted when a proposed authority accepts control of the registry
+#[event]
+pub struct AuthorityTransferred {
+    pub namespace: Pubkey,
+    pub previous_authority: Pubkey,
+    pub new_authority: Pubkey,
+}
+
+/// Audit trail for an authority-revoked nullifier
+#[event]
+pub struct NullifierRevoked {
+    pub nullifier_hash: [u8; 32],
+    pub namespace: Pubkey,
+    pub authority: Pubkey,
+    pub used_by: Pubkey,
+    pub claim_identifier_hash: [u8; 32],
+    pub reason_code: u8,
 }
 
 // ============================================================================
@@ -148,4 +2631,294 @@ pub enum NullifierError {
 
     #[msg("Nullifier hash mismatch")]
     NullifierHashMismatch,
+
+    #[msg("Caller is not allowed to mark nullifiers")]
+    UnauthorizedCaller,
+
+    #[msg("Too many allowed callers")]
+    TooManyAllowedCallers,
+
+    #[msg("Number of record accounts does not match number of nullifier hashes")]
+    BatchLengthMismatch,
+
+    #[msg("Nullifier record account does not match the expected PDA")]
+    InvalidNullifierAccount,
+
+    #[msg("Nullifier TTL must not be negative")]
+    InvalidTtl,
+
+    #[msg("Nullifier has not expired")]
+    NullifierNotExpired,
+
+    #[msg("Signer is not the pending authority")]
+    NotPendingAuthority,
+
+    #[msg("Nullifier registry is paused")]
+    RegistryPaused,
+
+    #[msg("Compressed nullifier set is full")]
+    CompressedSetFull,
+
+    #[msg("Invalid Merkle non-inclusion proof")]
+    InvalidMerkleProof,
+
+    #[msg("A non-zero reason code is required")]
+    MissingReasonCode,
+
+    #[msg("Nullifier is reserved by another pending flow")]
+    NullifierCurrentlyReserved,
+
+    #[msg("Reservation TTL must be positive and within the maximum")]
+    InvalidReservationTtl,
+
+    #[msg("Nullifier is not reserved")]
+    NotReserved,
+
+    #[msg("Reservation has expired")]
+    ReservationExpired,
+
+    #[msg("Reservation is owned by another user")]
+    ReservationNotOwned,
+
+    #[msg("User exceeded the namespace mark rate limit")]
+    RateLimitExceeded,
+
+    #[msg("Registry has a rate limit; the user's rate-limit account is required")]
+    RateLimitAccountRequired,
+
+    #[msg("Rate-limit window is still active")]
+    RateLimitWindowActive,
+
+    #[msg("Nullifier record is already in the current layout")]
+    RecordAlreadyMigrated,
+
+    #[msg("Registry id must be the authority's next unused id")]
+    InvalidRegistryId,
+
+    #[msg("Claim timestamp is in the future")]
+    InvalidClaimTimestamp,
+
+    #[msg("Claim is older than the registry retention window")]
+    ClaimOutsideRetention,
+
+    #[msg("Program is already in the caller allowlist")]
+    CallerAlreadyAllowed,
+
+    #[msg("Program is not in the caller allowlist")]
+    CallerNotAllowed,
+
+    #[msg("Registry has a bloom filter that must be passed when marking")]
+    BloomFilterRequired,
+
+    #[msg("Bloom filter can only be created before any nullifier is marked")]
+    BloomFilterAfterMarks,
+
+    #[msg("Compressed set can only be created before any nullifier is marked")]
+    CompressedSetAfterMarks,
+
+    #[msg("Registry uses the compressed nullifier set")]
+    CompressedRegistry,
+
+    #[msg("Nullifier width differs from the width this registry is pinned to")]
+    HashWidthMismatch,
+
+    #[msg("Caller authority is not the registry_caller PDA of the caller program")]
+    InvalidCallerAuthority,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn empty_bloom_filter() -> BloomFilter {
+        BloomFilter {
+            registry: Pubkey::default(),
+            bits: [0u8; BLOOM_FILTER_BYTES],
+        }
+    }
+
+    #[test]
+    fn bloom_filter_contains_inserted_hash() {
+        let mut bloom_filter = empty_bloom_filter();
+        let nullifier_hash = [7u8; 32];
+        assert!(!bloom_filter.contains(&nullifier_hash));
+
+        bloom_filter.insert(&nullifier_hash);
+        assert!(bloom_filter.contains(&nullifier_hash));
+    }
+
+    #[test]
+    fn bloom_filter_distinguishes_unrelated_hash() {
+        let mut bloom_filter = empty_bloom_filter();
+        let mut nullifier_hash = [0u8; 32];
+        nullifier_hash[..12].copy_from_slice(&[1, 0, 0, 0, 2, 0, 0, 0, 3, 0, 0, 0]);
+        bloom_filter.insert(&nullifier_hash);
+
+        let mut other_hash = [0u8; 32];
+        other_hash[..12].copy_from_slice(&[4, 0, 0, 0, 5, 0, 0, 0, 6, 0, 0, 0]);
+        assert!(!bloom_filter.contains(&other_hash));
+    }
+
+    #[test]
+    fn bloom_filter_has_no_false_negatives() {
+        let mut bloom_filter = empty_bloom_filter();
+        let marked: Vec<[u8; 32]> = (0..500).map(|i| hash_fields(&[&i.to_string()])).collect();
+
+        for (i, nullifier_hash) in marked.iter().enumerate() {
+            bloom_filter.insert(nullifier_hash);
+            // Every hash marked so far still probes true after later inserts
+            assert!(marked[..=i].iter().all(|hash| bloom_filter.contains(hash)));
+        }
+    }
+
+    #[test]
+    fn reservation_lapses_at_reserved_until() {
+        let record = NullifierRecord {
+            version: NULLIFIER_RECORD_VERSION,
+            nullifier_hash: [1u8; 32],
+            used_at: 100,
+            used_by: Pubkey::default(),
+            claim_identifier_hash: [0u8; 32],
+            marked_by_program: Pubkey::default(),
+            expires_at: None,
+            reserved_until: Some(160),
+            hash_width: 32,
+            sequence: 0,
+            reserved: [0u8; NULLIFIER_RECORD_RESERVED_BYTES],
+        };
+        assert!(record.is_held(159));
+        assert!(!record.is_expired(159));
+        assert!(!record.is_held(160));
+        assert!(record.is_expired(160));
+
+        let finalized = NullifierRecord {
+            reserved_until: None,
+            ..record
+        };
+        assert!(finalized.is_held(i64::MAX));
+        assert!(!finalized.is_expired(i64::MAX));
+    }
+
+    #[test]
+    fn expired_record_no_longer_blocks_nullifier() {
+        let record = NullifierRecord {
+            version: NULLIFIER_RECORD_VERSION,
+            nullifier_hash: [2u8; 32],
+            used_at: 100,
+            used_by: Pubkey::default(),
+            claim_identifier_hash: [0u8; 32],
+            marked_by_program: Pubkey::default(),
+            expires_at: Some(200),
+            reserved_until: None,
+            hash_width: 32,
+            sequence: 1,
+            reserved: [0u8; NULLIFIER_RECORD_RESERVED_BYTES],
+        };
+        assert!(record.is_live(199));
+        assert!(!record.is_live(200));
+
+        let permanent = NullifierRecord {
+            expires_at: None,
+            ..record
+        };
+        assert!(permanent.is_live(i64::MAX));
+    }
+
+    #[test]
+    fn nullifier_hash_widths_use_distinct_domains() {
+        let hash16 = NullifierHash::Hash16([5u8; 16]);
+        let mut padded = [0u8; 32];
+        padded[..16].copy_from_slice(&[5u8; 16]);
+
+        assert_eq!(hash16.to_stored(), padded);
+        assert_eq!(hash16.width(), 16);
+        assert_ne!(
+            hash16.seed_domain(),
+            NullifierHash::Hash32(padded).seed_domain()
+        );
+    }
+
+    #[test]
+    fn rate_limit_resets_each_hour() {
+        let mut rate_limit = UserRateLimit {
+            registry: Pubkey::default(),
+            user: Pubkey::default(),
+            current_hour: 0,
+            hourly_count: 0,
+        };
+        let now = 10 * UserRateLimit::SECONDS_PER_HOUR;
+
+        assert!(rate_limit.record_marks(2, 3, now));
+        assert!(!rate_limit.record_marks(2, 3, now + 1));
+        assert!(rate_limit.record_marks(1, 3, now + 2));
+        assert!(!rate_limit.record_marks(1, 3, now + 3));
+
+        assert!(rate_limit.record_marks(3, 3, now + UserRateLimit::SECONDS_PER_HOUR));
+        assert!(rate_limit.record_marks(100, 0, now + UserRateLimit::SECONDS_PER_HOUR));
+    }
+
+    #[test]
+    fn registry_pins_the_first_hash_width() {
+        let mut registry = NullifierRegistry {
+            creator: Pubkey::default(),
+            registry_id: 0,
+            namespace: Pubkey::default(),
+            authority: Pubkey::default(),
+            nullifier_count: 0,
+            nullifier_ttl: 0,
+            rent_recipient: Pubkey::default(),
+            pending_authority: None,
+            paused: false,
+            max_marks_per_hour: 0,
+            has_bloom_filter: false,
+            compressed: false,
+            hash_width: 0,
+        };
+        assert!(registry.accepts_hash_width(16));
+        assert!(registry.accepts_hash_width(32));
+
+        registry.pin_hash_width(32).unwrap();
+        assert!(registry.pin_hash_width(32).is_ok());
+        assert!(!registry.accepts_hash_width(16));
+        assert!(registry.pin_hash_width(16).is_err());
+        assert_eq!(registry.hash_width, 32);
+    }
+
+    #[test]
+    fn legacy_record_parses_only_the_80_byte_layout() {
+        let used_by = Pubkey::new_unique();
+        let mut data = NullifierRecord::DISCRIMINATOR.to_vec();
+        data.extend_from_slice(&[5u8; 32]);
+        data.extend_from_slice(&1_700_000_000i64.to_le_bytes());
+        data.extend_from_slice(used_by.as_ref());
+        assert_eq!(data.len(), LegacyNullifierRecord::SPACE);
+
+        let legacy = LegacyNullifierRecord::try_from_account_data(&data).unwrap();
+        assert_eq!(legacy.nullifier_hash, [5u8; 32]);
+        assert_eq!(legacy.used_at, 1_700_000_000);
+        assert_eq!(legacy.used_by, used_by);
+
+        data.push(0);
+        assert!(LegacyNullifierRecord::try_from_account_data(&data).is_err());
+        data.pop();
+        data[0] ^= 1;
+        assert!(LegacyNullifierRecord::try_from_account_data(&data).is_err());
+    }
+
+    #[test]
+    fn rate_limit_window_ends_with_the_hour_or_the_limit() {
+        let mut rate_limit = UserRateLimit {
+            registry: Pubkey::default(),
+            user: Pubkey::default(),
+            current_hour: 0,
+            hourly_count: 0,
+        };
+        let now = 10 * UserRateLimit::SECONDS_PER_HOUR;
+        assert!(!rate_limit.window_active(3, now));
+
+        assert!(rate_limit.record_marks(1, 3, now));
+        assert!(rate_limit.window_active(3, now + 1));
+        assert!(!rate_limit.window_active(0, now + 1));
+        assert!(!rate_limit.window_active(3, now + UserRateLimit::SECONDS_PER_HOUR));
+    }
 }
@@ -0,0 +1,253 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::keccak::hashv;
+
+/// Domain separators so leaves can never be confused with internal nodes
+const LEAF_PREFIX: &[u8] = &[0x00];
+const NODE_PREFIX: &[u8] = &[0x01];
+
+/// Depth of every compressed nullifier tree (2^20 leaves); proofs have exactly this many siblings
+pub const MERKLE_DEPTH: usize = 20;
+
+/// Number of leaves a compressed nullifier tree can hold (including the sentinel)
+pub const MAX_MERKLE_LEAVES: u64 = 1 << MERKLE_DEPTH;
+
+/// Node value of an unoccupied leaf slot
+pub const EMPTY_LEAF: [u8; 32] = [0u8; 32];
+
+/// Leaf of the indexed tree: the set is kept as a sorted linked list, each nullifier
+/// pointing at the next larger one, so inserting never moves existing leaves
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct IndexedLeaf {
+    pub value: [u8; 32],
+    /// Next larger nullifier in the set (all zero = none, `value` is the largest)
+    pub next_value: [u8; 32],
+}
+
+impl IndexedLeaf {
+    /// The zero-valued leaf every tree starts with, so each nullifier has a lower neighbour
+    pub const SENTINEL: IndexedLeaf = IndexedLeaf {
+        value: [0u8; 32],
+        next_value: [0u8; 32],
+    };
+
+    /// keccak256(0x00 || value || next_value)
+    pub fn hash(&self) -> [u8; 32] {
+        hashv(&[LEAF_PREFIX, &self.value, &self.next_value]).to_bytes()
+    }
+
+    /// Whether `value` lies strictly between this leaf and its successor,
+    /// i.e. it is not in the set if this leaf is
+    pub fn brackets(&self, value: &[u8; 32]) -> bool {
+        self.value < *value && (self.next_value == [0u8; 32] || *value < self.next_value)
+    }
+}
+
+/// Hash two child nodes: keccak256(0x01 || left || right)
+pub fn hash_node(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    hashv(&[NODE_PREFIX, left, right]).to_bytes()
+}
+
+/// Roots of empty subtrees by height (height 0 is an empty leaf)
+pub fn empty_subtree_roots() -> [[u8; 32]; MERKLE_DEPTH] {
+    let mut roots = [EMPTY_LEAF; MERKLE_DEPTH];
+    for height in 1..MERKLE_DEPTH {
+        roots[height] = hash_node(&roots[height - 1], &roots[height - 1]);
+    }
+    roots
+}
+
+/// Recompute the root from a leaf node, its index and the sibling path (leaf to root)
+pub fn compute_root(leaf: &[u8; 32], index: u64, proof: &[[u8; 32]]) -> [u8; 32] {
+    let mut node = *leaf;
+    let mut index = index;
+
+    for sibling in proof {
+        node = if index % 2 == 0 {
+            hash_node(&node, sibling)
+        } else {
+            hash_node(sibling, &node)
+        };
+        index /= 2;
+    }
+
+    node
+}
+
+/// Verify that `leaf` is the node at `index` in the tree with the given root
+pub fn verify_inclusion(root: &[u8; 32], leaf: &[u8; 32], index: u64, proof: &[[u8; 32]]) -> bool {
+    proof.len() == MERKLE_DEPTH
+        && index < MAX_MERKLE_LEAVES
+        && compute_root(leaf, index, proof) == *root
+}
+
+/// Replace the leaf at `index` (already proven by `proof`) and keep the frontier in sync
+/// `filled_subtrees[h]` holds the completed left subtree on the path to `leaf_count`,
+/// which must be rewritten when the updated leaf lies under it
+pub fn update_leaf(
+    filled_subtrees: &mut [[u8; 32]; MERKLE_DEPTH],
+    leaf_count: u64,
+    index: u64,
+    leaf: &[u8; 32],
+    proof: &[[u8; 32]],
+) -> [u8; 32] {
+    let mut node = *leaf;
+    let mut index = index;
+
+    for (height, sibling) in proof.iter().enumerate() {
+        let frontier = leaf_count >> height;
+        if frontier % 2 == 1 && index == frontier - 1 {
+            filled_subtrees[height] = node;
+        }
+        node = if index % 2 == 0 {
+            hash_node(&node, sibling)
+        } else {
+            hash_node(sibling, &node)
+        };
+        index /= 2;
+    }
+
+    node
+}
+
+/// Append `leaf` at `leaf_count` using the stored frontier and return the new root
+pub fn append_leaf(
+    filled_subtrees: &mut [[u8; 32]; MERKLE_DEPTH],
+    leaf_count: u64,
+    leaf: &[u8; 32],
+) -> [u8; 32] {
+    let empty_roots = empty_subtree_roots();
+    let mut node = *leaf;
+    let mut index = leaf_count;
+
+    for (height, filled) in filled_subtrees.iter_mut().enumerate() {
+        node = if index % 2 == 0 {
+            *filled = node;
+            hash_node(&node, &empty_roots[height])
+        } else {
+            hash_node(filled, &node)
+        };
+        index /= 2;
+    }
+
+    node
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Root and sibling path of `index` for a tree whose first leaves are `leaves`
+    fn root_and_proof(leaves: &[[u8; 32]], index: usize) -> ([u8; 32], Vec<[u8; 32]>) {
+        let empty_roots = empty_subtree_roots();
+        let mut level = leaves.to_vec();
+        let mut index = index;
+        let mut proof = Vec::with_capacity(MERKLE_DEPTH);
+
+        for empty_root in empty_roots.iter() {
+            proof.push(*level.get(index ^ 1).unwrap_or(empty_root));
+            level = level
+                .chunks(2)
+                .map(|pair| hash_node(&pair[0], pair.get(1).unwrap_or(empty_root)))
+                .collect();
+            index /= 2;
+        }
+
+        (level[0], proof)
+    }
+
+    fn value(fill: u8) -> [u8; 32] {
+        [fill; 32]
+    }
+
+    /// Insert `values` one by one the way `append_nullifier` does, checking the
+    /// incremental root against a full recomputation after every insert
+    fn insert_all(values: &[[u8; 32]]) -> Vec<IndexedLeaf> {
+        let mut filled_subtrees = [EMPTY_LEAF; MERKLE_DEPTH];
+        let mut leaves = vec![IndexedLeaf::SENTINEL];
+        let mut root = append_leaf(&mut filled_subtrees, 0, &IndexedLeaf::SENTINEL.hash());
+
+        for new_value in values {
+            let hashes: Vec<[u8; 32]> = leaves.iter().map(IndexedLeaf::hash).collect();
+            let low_index = leaves
+                .iter()
+                .position(|leaf| leaf.brackets(new_value))
+                .unwrap();
+            let (expected_root, low_proof) = root_and_proof(&hashes, low_index);
+            assert_eq!(root, expected_root);
+            assert!(verify_inclusion(
+                &root,
+                &hashes[low_index],
+                low_index as u64,
+                &low_proof
+            ));
+
+            let low_leaf = leaves[low_index];
+            let leaf_count = leaves.len() as u64;
+            leaves[low_index].next_value = *new_value;
+            update_leaf(
+                &mut filled_subtrees,
+                leaf_count,
+                low_index as u64,
+                &leaves[low_index].hash(),
+                &low_proof,
+            );
+            let new_leaf = IndexedLeaf {
+                value: *new_value,
+                next_value: low_leaf.next_value,
+            };
+            root = append_leaf(&mut filled_subtrees, leaf_count, &new_leaf.hash());
+            leaves.push(new_leaf);
+        }
+
+        let hashes: Vec<[u8; 32]> = leaves.iter().map(IndexedLeaf::hash).collect();
+        assert_eq!(root, root_and_proof(&hashes, 0).0);
+        leaves
+    }
+
+    #[test]
+    fn append_matches_full_recomputation() {
+        let values: Vec<[u8; 32]> = [0x50, 0x10, 0xf0, 0x30, 0x70, 0x20, 0x60, 0x40, 0x05]
+            .iter()
+            .map(|fill| value(*fill))
+            .collect();
+        let leaves = insert_all(&values);
+
+        // The linked list stays sorted: following next_value from the sentinel visits every value
+        let mut sorted = values.clone();
+        sorted.sort();
+        let mut current = IndexedLeaf::SENTINEL.value;
+        for expected in sorted {
+            let leaf = leaves.iter().find(|leaf| leaf.value == current).unwrap();
+            assert_eq!(leaf.next_value, expected);
+            current = expected;
+        }
+    }
+
+    #[test]
+    fn inserted_values_are_no_longer_bracketed() {
+        let values = [value(0x10), value(0x20)];
+        let leaves = insert_all(&values);
+
+        for inserted in values {
+            assert!(!leaves.iter().any(|leaf| leaf.brackets(&inserted)));
+        }
+        assert!(leaves.iter().any(|leaf| leaf.brackets(&value(0x15))));
+        assert!(leaves.iter().any(|leaf| leaf.brackets(&value(0x30))));
+    }
+
+    #[test]
+    fn verify_inclusion_rejects_wrong_index_or_short_proof() {
+        let hashes = [IndexedLeaf::SENTINEL.hash(), value(0x10)];
+        let (root, proof) = root_and_proof(&hashes, 1);
+
+        assert!(verify_inclusion(&root, &hashes[1], 1, &proof));
+        assert!(!verify_inclusion(&root, &hashes[1], 0, &proof));
+        assert!(!verify_inclusion(&root, &hashes[0], 1, &proof));
+        assert!(!verify_inclusion(
+            &root,
+            &hashes[1],
+            1,
+            &proof[..MERKLE_DEPTH - 1]
+        ));
+    }
+}
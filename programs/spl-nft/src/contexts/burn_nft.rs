@@ -0,0 +1,187 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program_option::COption;
+use anchor_spl::metadata::mpl_token_metadata::instructions::{
+    BurnV1Cpi, BurnV1CpiAccounts, BurnV1InstructionArgs, ThawDelegatedAccountCpi,
+    ThawDelegatedAccountCpiAccounts,
+};
+use anchor_spl::{
+    metadata::{Metadata, MetadataAccount},
+    token::{Mint, Token, TokenAccount},
+};
+
+use super::close_collection::close_account;
+use super::create_collection::CollectionState;
+use super::mint_nft::MintRecord;
+use super::verify_collection::INSTRUCTIONS_ID;
+use crate::errors::SplNftError;
+use crate::events::NftBurned;
+
+#[derive(Accounts)]
+pub struct BurnNFT<'info> {
+    /// The holder, or the collection authority acting as the holder's delegate
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        seeds = [b"authority"],
+        bump,
+    )]
+    /// CHECK: This is account is not initialized and is being used for signing purposes only
+    pub mint_authority: UncheckedAccount<'info>,
+    pub collection_mint: Account<'info, Mint>,
+    /// Only required for burns by the collection authority, so holders can still
+    /// burn after the collection is closed
+    #[account(
+        seeds = [b"collection_state", collection_mint.key().as_ref()],
+        bump,
+    )]
+    pub collection_state: Option<Account<'info, CollectionState>>,
+    #[account(mut)]
+    pub mint: Account<'info, Mint>,
+    #[account(
+        mut,
+        token::mint = mint,
+    )]
+    pub token: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        address = token.owner,
+    )]
+    /// CHECK: Owner of the token account, refunded the rent of the NFT's program accounts
+    pub holder: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        constraint = metadata.collection.as_ref().map(|c| c.key) == Some(collection_mint.key())
+            @ SplNftError::CollectionMismatch,
+    )]
+    pub metadata: Account<'info, MetadataAccount>,
+    #[account(mut)]
+    /// CHECK: This account will be closed by the metaplex program
+    pub master_edition: UncheckedAccount<'info>,
+    #[account(mut)]
+    /// CHECK: Validated by the metaplex program; its size is decremented for verified items
+    pub collection_metadata: UncheckedAccount<'info>,
+    #[account(mut)]
+    /// CHECK: Token record PDA, only used for programmable NFTs
+    pub token_record: Option<UncheckedAccount<'info>>,
+    #[account(
+        mut,
+        seeds = [b"mint_record", mint.key().as_ref()],
+        bump,
+        has_one = collection_mint @ SplNftError::CollectionMismatch,
+        close = holder,
+    )]
+    pub mint_record: Account<'info, MintRecord>,
+    #[account(
+        mut,
+        seeds = [b"attributes", mint.key().as_ref()],
+        bump,
+    )]
+    /// CHECK: Claim attributes PDA, closed when the NFT was minted with attributes
+    pub nft_attributes: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+    #[account(address = INSTRUCTIONS_ID)]
+    /// CHECK: Sysvar instruction account that is being checked with an address constraint
+    pub sysvar_instruction: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
+    pub token_metadata_program: Program<'info, Metadata>,
+}
+
+impl<'info> BurnNFT<'info> {
+    /// Burn the token, close the metadata and edition, and refund the mint record
+    /// and claim attributes accounts to the holder.
+    /// The collection authority burns through the authority PDA, which the holder
+    /// approved as the token's delegate when signing the mint. Frozen (soulbound)
+    /// tokens are thawed through the master edition first.
+    pub fn burn_nft(&mut self, bumps: &BurnNFTBumps) -> Result<()> {
+        let holder = self.token.owner;
+        let authority_key = self.authority.key();
+        let delegated = self.token.delegate == COption::Some(self.mint_authority.key());
+        let by_holder = authority_key == holder;
+        if !by_holder {
+            let collection_state = self
+                .collection_state
+                .as_ref()
+                .ok_or(SplNftError::UnauthorizedBurn)?;
+            require_keys_eq!(
+                authority_key,
+                collection_state.authority,
+                SplNftError::UnauthorizedBurn
+            );
+            require!(delegated, SplNftError::BurnNotDelegated);
+        }
+
+        let seeds = &[&b"authority"[..], &[bumps.mint_authority]];
+        let signer_seeds = &[&seeds[..]];
+
+        let collection_metadata = &self.collection_metadata.to_account_info();
+        let metadata = &self.metadata.to_account_info();
+        let master_edition = &self.master_edition.to_account_info();
+        let mint = &self.mint.to_account_info();
+        let token = &self.token.to_account_info();
+        let token_record = self.token_record.as_ref().map(|a| a.to_account_info());
+        let system_program = &self.system_program.to_account_info();
+        let sysvar_instructions = &self.sysvar_instruction.to_account_info();
+        let spl_token_program = &self.token_program.to_account_info();
+        let spl_metadata_program = &self.token_metadata_program.to_account_info();
+        let delegate = &self.mint_authority.to_account_info();
+
+        // Programmable NFTs are thawed by Token Metadata itself
+        if self.token.is_frozen() && delegated && token_record.is_none() {
+            ThawDelegatedAccountCpi::new(
+                spl_metadata_program,
+                ThawDelegatedAccountCpiAccounts {
+                    delegate,
+                    token_account: token,
+                    edition: master_edition,
+                    mint,
+                    token_program: spl_token_program,
+                },
+            )
+            .invoke_signed(signer_seeds)?;
+            msg!("NFT thawed");
+        }
+
+        let authority = if by_holder {
+            self.authority.to_account_info()
+        } else {
+            delegate.clone()
+        };
+
+        BurnV1Cpi::new(
+            spl_metadata_program,
+            BurnV1CpiAccounts {
+                authority: &authority,
+                collection_metadata: Some(collection_metadata),
+                metadata,
+                edition: Some(master_edition),
+                mint,
+                token,
+                master_edition: None,
+                master_edition_mint: None,
+                master_edition_token: None,
+                edition_marker: None,
+                token_record: token_record.as_ref(),
+                system_program,
+                sysvar_instructions,
+                spl_token_program,
+            },
+            BurnV1InstructionArgs { amount: 1 },
+        )
+        .invoke_signed(signer_seeds)?;
+        msg!("NFT burned!");
+
+        let nft_attributes = self.nft_attributes.to_account_info();
+        if *nft_attributes.owner == crate::ID {
+            close_account(&nft_attributes, &self.holder.to_account_info())?;
+        }
+
+        emit!(NftBurned {
+            collection_mint: self.collection_mint.key(),
+            mint: self.mint.key(),
+            holder,
+            authority: authority_key,
+        });
+
+        Ok(())
+    }
+}
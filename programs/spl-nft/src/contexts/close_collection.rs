@@ -0,0 +1,72 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::Mint;
+
+use super::create_collection::CollectionState;
+use super::mint_nft::WalletMintCounter;
+use crate::errors::SplNftError;
+use crate::events::CollectionClosed;
+
+#[derive(Accounts)]
+pub struct CloseCollection<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub collection_mint: Account<'info, Mint>,
+    #[account(
+        mut,
+        seeds = [b"collection_state", collection_mint.key().as_ref()],
+        bump,
+        has_one = authority,
+        close = authority,
+    )]
+    pub collection_state: Account<'info, CollectionState>,
+}
+
+impl<'info> CloseCollection<'info> {
+    /// Close the collection state and the given wallet mint counters, returning their
+    /// rent to the authority. Per-mint records and attributes stay with their NFTs
+    /// until a burn refunds them to the holder.
+    pub fn close_collection(&mut self, wallet_mint_counters: &[AccountInfo<'info>]) -> Result<()> {
+        require!(
+            self.collection_state.counter == 0 || self.collection_state.sunset,
+            SplNftError::CollectionStillActive
+        );
+
+        let collection_mint = self.collection_mint.key();
+        let authority = self.authority.to_account_info();
+        for counter_info in wallet_mint_counters {
+            require_keys_eq!(
+                *counter_info.owner,
+                crate::ID,
+                SplNftError::InvalidWalletMintCounter
+            );
+            let counter = WalletMintCounter::try_deserialize(&mut &counter_info.data.borrow()[..])?;
+            require_keys_eq!(
+                counter.collection_mint,
+                collection_mint,
+                SplNftError::InvalidWalletMintCounter
+            );
+            close_account(counter_info, &authority)?;
+        }
+
+        emit!(CollectionClosed {
+            collection_mint,
+            authority: authority.key(),
+            closed_wallet_counters: wallet_mint_counters.len() as u32,
+        });
+
+        Ok(())
+    }
+}
+
+/// Move all lamports to `destination` and hand the emptied account back to the system program
+pub(crate) fn close_account<'info>(
+    info: &AccountInfo<'info>,
+    destination: &AccountInfo<'info>,
+) -> Result<()> {
+    let lamports = info.lamports();
+    **destination.try_borrow_mut_lamports()? += lamports;
+    **info.try_borrow_mut_lamports()? = 0;
+    info.assign(&System::id());
+    info.resize(0)?;
+    Ok(())
+}
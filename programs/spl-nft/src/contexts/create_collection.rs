@@ -13,15 +13,98 @@ use anchor_spl::{
     token::{mint_to, Mint, MintTo, Token, TokenAccount},
 };
 
+use crate::errors::SplNftError;
+
+/// Maximum lengths follow the Metaplex metadata limits
+pub const MAX_NAME_LEN: usize = 32;
+pub const MAX_SYMBOL_LEN: usize = 10;
+pub const MAX_URI_LEN: usize = 200;
+/// Metaplex allows 5 creators; one slot is kept for the mint authority PDA
+pub const MAX_CREATORS: usize = 4;
+pub const MAX_SELLER_FEE_BASIS_POINTS: u16 = 10_000;
+
+/// A royalty recipient listed in the metadata of every minted NFT
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct CollectionCreator {
+    pub address: Pubkey,
+    pub share: u8,
+}
+
+/// Settings chosen by the authority when creating a collection
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct CollectionConfig {
+    pub name: String,
+    pub symbol: String,
+    pub collection_uri: String,
+    pub uri_prefix: String,
+    pub price: u64,
+    /// Maximum NFTs a single wallet may receive; 0 means unlimited
+    pub max_mints_per_wallet: u32,
+    /// Maximum number of NFTs in the collection; 0 means unlimited
+    pub max_supply: u64,
+    pub soulbound: bool,
+    pub programmable: bool,
+    pub rule_set: Option<Pubkey>,
+    pub seller_fee_basis_points: u16,
+    pub creators: Vec<CollectionCreator>,
+    pub gated_minter: Option<Pubkey>,
+}
+
 #[account]
+#[derive(InitSpace)]
 pub struct CollectionState {
     pub collection_mint: Pubkey,
+    /// May update the collection settings
+    pub authority: Pubkey,
+    #[max_len(MAX_NAME_LEN)]
     pub name: String,
+    #[max_len(MAX_SYMBOL_LEN)]
     pub symbol: String,
+    #[max_len(MAX_URI_LEN)]
     pub uri_prefix: String,
+    #[max_len(MAX_URI_LEN)]
     pub collection_uri: String,
     pub counter: u64,
     pub price: u64,
+    /// Maximum NFTs a single wallet may receive; 0 means unlimited
+    pub max_mints_per_wallet: u32,
+    /// Maximum number of NFTs in the collection; 0 means unlimited
+    pub max_supply: u64,
+    /// Freeze minted NFTs in the recipient's wallet so they cannot be transferred
+    pub soulbound: bool,
+    /// Mint items as programmable NFTs (pNFTs)
+    pub programmable: bool,
+    /// Token Auth rule set enforced on programmable NFT transfers
+    pub rule_set: Option<Pubkey>,
+    /// Royalty applied to minted NFTs
+    pub seller_fee_basis_points: u16,
+    #[max_len(MAX_CREATORS)]
+    pub creators: Vec<CollectionCreator>,
+    /// Set by the collection authority to stop all mints
+    pub mint_paused: bool,
+    /// When set, `mint_nft` must be invoked via CPI from this program
+    pub gated_minter: Option<Pubkey>,
+    /// Permanently retired by the authority; no more mints, may be closed
+    pub sunset: bool,
+}
+
+impl CollectionState {
+    /// Metadata creators for a minted NFT. The mint authority PDA is always listed
+    /// (and verified); it takes the full share when no creators are configured.
+    pub fn metadata_creators(&self, mint_authority: Pubkey) -> Vec<Creator> {
+        let authority_share = if self.creators.is_empty() { 100 } else { 0 };
+        let mut creators = vec![Creator {
+            address: mint_authority,
+            verified: true,
+            share: authority_share,
+        }];
+        creators.extend(self.creators.iter().map(|creator| Creator {
+            address: creator.address,
+            verified: false,
+            share: creator.share,
+        }));
+        creators
+    }
 }
 
 #[derive(Accounts)]
@@ -39,7 +122,7 @@ pub struct CreateCollection<'info> {
     #[account(
         init,
         payer = user,
-        space = 8 + 32 + 4 + 64 + 4 + 32 + 4 + 200 + 8 + 8,
+        space = 8 + CollectionState::INIT_SPACE,
         seeds = [b"collection_state", mint.key().as_ref()],
         bump,
     )]
@@ -73,12 +156,45 @@ impl<'info> CreateCollection<'info> {
     pub fn create_collection(
         &mut self,
         bumps: &CreateCollectionBumps,
-        name: String,
-        symbol: String,
-        collection_uri: String,
-        uri_prefix: String,
-        price: u64,
+        config: CollectionConfig,
     ) -> Result<()> {
+        let CollectionConfig {
+            name,
+            symbol,
+            collection_uri,
+            uri_prefix,
+            price,
+            max_mints_per_wallet,
+            max_supply,
+            soulbound,
+            programmable,
+            rule_set,
+            seller_fee_basis_points,
+            creators,
+            gated_minter,
+        } = config;
+
+        require!(name.len() <= MAX_NAME_LEN, SplNftError::NameTooLong);
+        require!(symbol.len() <= MAX_SYMBOL_LEN, SplNftError::SymbolTooLong);
+        require!(
+            collection_uri.len() <= MAX_URI_LEN && uri_prefix.len() <= MAX_URI_LEN,
+            SplNftError::UriTooLong
+        );
+        // pNFT token accounts are managed by Token Metadata, so they can't be frozen here
+        require!(
+            !(soulbound && programmable),
+            SplNftError::SoulboundProgrammableConflict
+        );
+        require!(
+            programmable || rule_set.is_none(),
+            SplNftError::InvalidRuleSet
+        );
+        require!(
+            seller_fee_basis_points <= MAX_SELLER_FEE_BASIS_POINTS,
+            SplNftError::InvalidSellerFee
+        );
+        validate_creators(&creators, self.mint_authority.key())?;
+
         let metadata = &self.metadata.to_account_info();
         let master_edition = &self.master_edition.to_account_info();
         let mint = &self.mint.to_account_info();
@@ -157,13 +273,45 @@ impl<'info> CreateCollection<'info> {
 
         // Initialize collection state
         self.collection_state.collection_mint = self.mint.key();
+        self.collection_state.authority = self.user.key();
         self.collection_state.name = name;
         self.collection_state.symbol = symbol;
         self.collection_state.collection_uri = collection_uri;
         self.collection_state.uri_prefix = uri_prefix;
         self.collection_state.counter = 0;
         self.collection_state.price = price;
+        self.collection_state.max_mints_per_wallet = max_mints_per_wallet;
+        self.collection_state.max_supply = max_supply;
+        self.collection_state.soulbound = soulbound;
+        self.collection_state.programmable = programmable;
+        self.collection_state.rule_set = rule_set;
+        self.collection_state.seller_fee_basis_points = seller_fee_basis_points;
+        self.collection_state.creators = creators;
+        self.collection_state.mint_paused = false;
+        self.collection_state.gated_minter = gated_minter;
+        self.collection_state.sunset = false;
 
         Ok(())
     }
 }
+
+/// Creators must be distinct, exclude the mint authority and split exactly 100 shares
+fn validate_creators(creators: &[CollectionCreator], mint_authority: Pubkey) -> Result<()> {
+    if creators.is_empty() {
+        return Ok(());
+    }
+    require!(creators.len() <= MAX_CREATORS, SplNftError::TooManyCreators);
+
+    let mut total_share: u16 = 0;
+    for (i, creator) in creators.iter().enumerate() {
+        require!(
+            creator.address != mint_authority
+                && creators[..i].iter().all(|c| c.address != creator.address),
+            SplNftError::InvalidCreators
+        );
+        total_share += u16::from(creator.share);
+    }
+    require!(total_share == 100, SplNftError::InvalidCreators);
+
+    Ok(())
+}
@@ -0,0 +1,126 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
+use anchor_spl::token::{Mint, TokenAccount};
+
+use super::create_collection::{CollectionState, MAX_NAME_LEN, MAX_SYMBOL_LEN, MAX_URI_LEN};
+use crate::errors::SplNftError;
+use crate::events::CollectionStateMigrated;
+
+/// Collection state written before collections had an authority and mint settings
+#[derive(AnchorDeserialize)]
+pub struct LegacyCollectionState {
+    pub collection_mint: Pubkey,
+    pub name: String,
+    pub symbol: String,
+    pub uri_prefix: String,
+    pub collection_uri: String,
+    pub counter: u64,
+    pub price: u64,
+}
+
+impl LegacyCollectionState {
+    /// Fixed account size allocated for the legacy layout
+    pub const SPACE: usize = 8 + 32 + 4 + 64 + 4 + 32 + 4 + 200 + 8 + 8;
+}
+
+#[derive(Accounts)]
+pub struct MigrateCollectionState<'info> {
+    /// Holder of the collection NFT; becomes the collection authority and funds the extra rent
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub collection_mint: Account<'info, Mint>,
+    #[account(
+        token::mint = collection_mint,
+        token::authority = authority,
+        constraint = collection_token.amount == 1 @ SplNftError::UnauthorizedMigration,
+    )]
+    pub collection_token: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        seeds = [b"collection_state", collection_mint.key().as_ref()],
+        bump,
+    )]
+    /// CHECK: Legacy layout; owner, discriminator and size are checked in the handler
+    pub collection_state: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> MigrateCollectionState<'info> {
+    /// Grow a legacy collection state to the current layout. The caller becomes the
+    /// collection authority; new settings start unlimited, transferable and ungated.
+    pub fn migrate_collection_state(&mut self) -> Result<()> {
+        let state_info = self.collection_state.to_account_info();
+        require_keys_eq!(
+            *state_info.owner,
+            crate::ID,
+            SplNftError::InvalidCollectionStateLayout
+        );
+        let legacy = {
+            let data = state_info.try_borrow_data()?;
+            require!(
+                data.len() == LegacyCollectionState::SPACE
+                    && data[..8] == *CollectionState::DISCRIMINATOR,
+                SplNftError::InvalidCollectionStateLayout
+            );
+            LegacyCollectionState::deserialize(&mut &data[8..])?
+        };
+        require_keys_eq!(
+            legacy.collection_mint,
+            self.collection_mint.key(),
+            SplNftError::InvalidCollectionStateLayout
+        );
+        require!(legacy.name.len() <= MAX_NAME_LEN, SplNftError::NameTooLong);
+        require!(
+            legacy.symbol.len() <= MAX_SYMBOL_LEN,
+            SplNftError::SymbolTooLong
+        );
+        require!(
+            legacy.uri_prefix.len() <= MAX_URI_LEN && legacy.collection_uri.len() <= MAX_URI_LEN,
+            SplNftError::UriTooLong
+        );
+
+        let state = CollectionState {
+            collection_mint: legacy.collection_mint,
+            authority: self.authority.key(),
+            name: legacy.name,
+            symbol: legacy.symbol,
+            uri_prefix: legacy.uri_prefix,
+            collection_uri: legacy.collection_uri,
+            counter: legacy.counter,
+            price: legacy.price,
+            max_mints_per_wallet: 0,
+            max_supply: 0,
+            soulbound: false,
+            programmable: false,
+            rule_set: None,
+            seller_fee_basis_points: 0,
+            creators: Vec::new(),
+            mint_paused: false,
+            gated_minter: None,
+            sunset: false,
+        };
+
+        let space = 8 + CollectionState::INIT_SPACE;
+        let top_up = Rent::get()?
+            .minimum_balance(space)
+            .saturating_sub(state_info.lamports());
+        if top_up > 0 {
+            let cpi_accounts = Transfer {
+                from: self.authority.to_account_info(),
+                to: state_info.clone(),
+            };
+            let cpi_ctx = CpiContext::new(self.system_program.to_account_info(), cpi_accounts);
+            transfer(cpi_ctx, top_up)?;
+        }
+        state_info.resize(space)?;
+        state.try_serialize(&mut &mut state_info.try_borrow_mut_data()?[..])?;
+
+        emit!(CollectionStateMigrated {
+            collection_mint: self.collection_mint.key(),
+            authority: self.authority.key(),
+            counter: state.counter,
+        });
+
+        Ok(())
+    }
+}
@@ -1,21 +1,73 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar::instructions::get_instruction_relative;
 use anchor_spl::metadata::mpl_token_metadata::{
     instructions::{
         CreateMasterEditionV3Cpi, CreateMasterEditionV3CpiAccounts,
         CreateMasterEditionV3InstructionArgs, CreateMetadataAccountV3Cpi,
-        CreateMetadataAccountV3CpiAccounts, CreateMetadataAccountV3InstructionArgs,
+        CreateMetadataAccountV3CpiAccounts, CreateMetadataAccountV3InstructionArgs, CreateV1Cpi,
+        CreateV1CpiAccounts, CreateV1InstructionArgs, MintV1Cpi, MintV1CpiAccounts,
+        MintV1InstructionArgs,
     },
-    types::{Collection, Creator, DataV2},
+    types::{Collection, Creator, DataV2, PrintSupply, TokenStandard},
 };
 use anchor_spl::{
     associated_token::AssociatedToken,
     metadata::Metadata,
-    token::{mint_to, Mint, MintTo, Token, TokenAccount},
+    token::{
+        approve, freeze_account, mint_to, Approve, FreezeAccount, Mint, MintTo, Token, TokenAccount,
+    },
 };
 
+use super::create_collection::{MAX_NAME_LEN, MAX_URI_LEN};
+use super::verify_collection::INSTRUCTIONS_ID;
+use crate::errors::SplNftError;
+use crate::events::CollectionSoldOut;
+
+/// Number of NFTs minted to one wallet from one collection
+#[account]
+#[derive(InitSpace)]
+pub struct WalletMintCounter {
+    pub collection_mint: Pubkey,
+    pub wallet: Pubkey,
+    pub count: u32,
+}
+
+/// Maximum claim identifier length (0x + 64 hex chars)
+pub const MAX_CLAIM_IDENTIFIER_LEN: usize = 66;
+
+/// What a minted NFT attests to, taken from the verified claim
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct ClaimAttributes {
+    #[max_len(MAX_CLAIM_IDENTIFIER_LEN)]
+    pub claim_identifier: String,
+    /// Order of magnitude of the payment amount (number of decimal digits)
+    pub amount_bucket: u8,
+    pub verified_at: i64,
+}
+
+/// Per-mint record of where an NFT sits in its collection
+#[account]
+#[derive(InitSpace)]
+pub struct MintRecord {
+    pub mint: Pubkey,
+    pub collection_mint: Pubkey,
+    /// 1-based position in the collection, also shown as `#n` in the name
+    pub edition_number: u64,
+}
+
+/// Companion account storing the claim attributes of one NFT
+#[account]
+#[derive(InitSpace)]
+pub struct NftAttributes {
+    pub mint: Pubkey,
+    pub attributes: ClaimAttributes,
+}
+
 #[derive(Accounts)]
 pub struct MintNFT<'info> {
-    /// The owner who will receive the NFT (doesn't need to sign)
+    /// The owner who will receive the NFT (only needs to sign soulbound mints).
+    /// When it signs a regular NFT mint, the authority PDA is approved as the
+    /// token's delegate so the collection authority can burn it later.
     /// CHECK: This is the recipient of the NFT
     pub owner: UncheckedAccount<'info>,
 
@@ -58,14 +110,178 @@ pub struct MintNFT<'info> {
         bump,
     )]
     pub collection_state: Account<'info, super::create_collection::CollectionState>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + WalletMintCounter::INIT_SPACE,
+        seeds = [b"wallet_mints", collection_mint.key().as_ref(), owner.key().as_ref()],
+        bump,
+    )]
+    pub wallet_mint_counter: Account<'info, WalletMintCounter>,
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + MintRecord::INIT_SPACE,
+        seeds = [b"mint_record", mint.key().as_ref()],
+        bump,
+    )]
+    pub mint_record: Account<'info, MintRecord>,
     pub system_program: Program<'info, System>,
     pub token_program: Program<'info, Token>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub token_metadata_program: Program<'info, Metadata>,
+
+    // Programmable NFT accounts, only required when the collection is programmable
+    #[account(mut)]
+    /// CHECK: Token record PDA, initialized by the metaplex program
+    pub token_record: Option<UncheckedAccount<'info>>,
+    /// Also required when the collection has a gated minter
+    #[account(address = INSTRUCTIONS_ID)]
+    /// CHECK: Sysvar instruction account that is being checked with an address constraint
+    pub sysvar_instruction: Option<UncheckedAccount<'info>>,
+    /// CHECK: Validated by the metaplex program
+    pub authorization_rules_program: Option<UncheckedAccount<'info>>,
+    /// CHECK: Checked against the collection's rule set
+    pub authorization_rules: Option<UncheckedAccount<'info>>,
+
+    /// Only passed when the gated minter mints with claim attributes
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + NftAttributes::INIT_SPACE,
+        seeds = [b"attributes", mint.key().as_ref()],
+        bump,
+    )]
+    pub nft_attributes: Option<Account<'info, NftAttributes>>,
 }
 
 impl<'info> MintNFT<'info> {
-    pub fn mint_nft(&mut self, bumps: &MintNFTBumps) -> Result<()> {
+    /// `name` and `uri` override the collection defaults for this mint only.
+    /// Overrides must extend the collection name and live under its URI prefix.
+    /// The edition number is appended to the name as ` #n`.
+    /// `attributes` are stored in the `nft_attributes` companion account.
+    pub fn mint_nft(
+        &mut self,
+        bumps: &MintNFTBumps,
+        name: Option<String>,
+        uri: Option<String>,
+        attributes: Option<ClaimAttributes>,
+    ) -> Result<()> {
+        require!(!self.collection_state.sunset, SplNftError::CollectionSunset);
+        require!(!self.collection_state.mint_paused, SplNftError::MintPaused);
+
+        // Gated collections reject direct calls: the top-level instruction must
+        // belong to the minter program, which then reaches us through CPI
+        if let Some(gated_minter) = self.collection_state.gated_minter {
+            let sysvar_instruction = self
+                .sysvar_instruction
+                .as_ref()
+                .ok_or(SplNftError::UnauthorizedMinter)?;
+            let current_ix = get_instruction_relative(0, &sysvar_instruction.to_account_info())?;
+            require_keys_eq!(
+                current_ix.program_id,
+                gated_minter,
+                SplNftError::UnauthorizedMinter
+            );
+        }
+
+        let max_supply = self.collection_state.max_supply;
+        require!(
+            max_supply == 0 || self.collection_state.counter < max_supply,
+            SplNftError::CollectionSoldOut
+        );
+
+        // Enforce the per-wallet limit before doing any CPI work
+        let max_mints = self.collection_state.max_mints_per_wallet;
+        require!(
+            max_mints == 0 || self.wallet_mint_counter.count < max_mints,
+            SplNftError::WalletMintLimitReached
+        );
+        self.wallet_mint_counter.collection_mint = self.collection_mint.key();
+        self.wallet_mint_counter.wallet = self.owner.key();
+        self.wallet_mint_counter.count += 1;
+
+        match (attributes, self.nft_attributes.as_mut()) {
+            (Some(attributes), Some(nft_attributes)) => {
+                // Only the gated minter, checked above, vouches for the claim
+                require!(
+                    self.collection_state.gated_minter.is_some(),
+                    SplNftError::AttributesRequireGatedMinter
+                );
+                require!(
+                    attributes.claim_identifier.len() <= MAX_CLAIM_IDENTIFIER_LEN,
+                    SplNftError::ClaimIdentifierTooLong
+                );
+                nft_attributes.mint = self.mint.key();
+                nft_attributes.attributes = attributes;
+            }
+            (None, None) => {}
+            _ => return err!(SplNftError::AttributesAccountMismatch),
+        }
+
+        let seeds = &[&b"authority"[..], &[bumps.mint_authority]];
+        let signer_seeds = &[&seeds[..]];
+
+        let creator = self
+            .collection_state
+            .metadata_creators(self.mint_authority.key());
+
+        // Increment counter and build URI
+        self.collection_state.counter += 1;
+        let token_id = self.collection_state.counter;
+        if token_id == max_supply {
+            emit!(CollectionSoldOut {
+                collection_mint: self.collection_mint.key(),
+                max_supply,
+            });
+        }
+        self.mint_record.mint = self.mint.key();
+        self.mint_record.collection_mint = self.collection_mint.key();
+        self.mint_record.edition_number = token_id;
+
+        // Remove trailing slash from uri_prefix if present to avoid double slashes
+        let uri_prefix = self.collection_state.uri_prefix.trim_end_matches('/');
+        let uri = match uri {
+            Some(uri) => {
+                require!(
+                    uri.len() <= MAX_URI_LEN
+                        && uri
+                            .strip_prefix(uri_prefix)
+                            .is_some_and(|path| path.len() > 1 && path.starts_with('/')),
+                    SplNftError::InvalidUriOverride
+                );
+                uri
+            }
+            None => format!("{}/{}.json", uri_prefix, token_id),
+        };
+
+        let name = match name {
+            Some(name) => {
+                require!(
+                    name.len() <= MAX_NAME_LEN && name.starts_with(&self.collection_state.name),
+                    SplNftError::InvalidNameOverride
+                );
+                name
+            }
+            None => self.collection_state.name.clone(),
+        };
+        let name = numbered_name(&name, token_id);
+
+        if self.collection_state.programmable {
+            self.mint_programmable(name, uri, creator, signer_seeds)
+        } else {
+            self.mint_standard(name, uri, creator, signer_seeds)
+        }
+    }
+
+    /// Mint a regular NFT: token first, then metadata and master edition
+    fn mint_standard(
+        &self,
+        name: String,
+        uri: String,
+        creator: Vec<Creator>,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<()> {
         let metadata = &self.metadata.to_account_info();
         let master_edition = &self.master_edition.to_account_info();
         let mint = &self.mint.to_account_info();
@@ -75,9 +291,6 @@ impl<'info> MintNFT<'info> {
         let spl_token_program = &self.token_program.to_account_info();
         let spl_metadata_program = &self.token_metadata_program.to_account_info();
 
-        let seeds = &[&b"authority"[..], &[bumps.mint_authority]];
-        let signer_seeds = &[&seeds[..]];
-
         let cpi_program = self.token_program.to_account_info();
         let cpi_accounts = MintTo {
             mint: self.mint.to_account_info(),
@@ -88,19 +301,39 @@ impl<'info> MintNFT<'info> {
         mint_to(cpi_ctx, 1)?;
         msg!("Collection NFT minted!");
 
-        let creator = vec![Creator {
-            address: self.mint_authority.key(),
-            verified: true,
-            share: 100,
-        }];
+        // Soulbound NFTs can only be thawed (to be burned) through the delegate
+        require!(
+            !self.collection_state.soulbound || self.owner.is_signer,
+            SplNftError::SoulboundMintUnsigned
+        );
 
-        // Increment counter and build URI
-        self.collection_state.counter += 1;
-        let token_id = self.collection_state.counter;
+        // Approve before freezing: a frozen account can't change its delegate
+        if self.owner.is_signer {
+            let cpi_accounts = Approve {
+                to: self.destination.to_account_info(),
+                delegate: self.mint_authority.to_account_info(),
+                authority: self.owner.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new(self.token_program.to_account_info(), cpi_accounts);
+            approve(cpi_ctx, 1)?;
+        }
 
-        // Remove trailing slash from uri_prefix if present to avoid double slashes
-        let uri_prefix = self.collection_state.uri_prefix.trim_end_matches('/');
-        let uri = format!("{}/{}.json", uri_prefix, token_id);
+        // Freeze while the authority PDA still holds the freeze authority;
+        // the master edition takes it over below
+        if self.collection_state.soulbound {
+            let cpi_accounts = FreezeAccount {
+                account: self.destination.to_account_info(),
+                mint: self.mint.to_account_info(),
+                authority: self.mint_authority.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                cpi_accounts,
+                signer_seeds,
+            );
+            freeze_account(cpi_ctx)?;
+            msg!("NFT frozen (soulbound)");
+        }
 
         let metadata_account = CreateMetadataAccountV3Cpi::new(
             spl_metadata_program,
@@ -115,10 +348,10 @@ impl<'info> MintNFT<'info> {
             },
             CreateMetadataAccountV3InstructionArgs {
                 data: DataV2 {
-                    name: self.collection_state.name.clone(),
+                    name,
                     symbol: self.collection_state.symbol.clone(),
                     uri,
-                    seller_fee_basis_points: 0,
+                    seller_fee_basis_points: self.collection_state.seller_fee_basis_points,
                     creators: Some(creator),
                     collection: Some(Collection {
                         verified: false,
@@ -153,4 +386,132 @@ impl<'info> MintNFT<'info> {
 
         Ok(())
     }
+
+    /// Mint a programmable NFT through the Token Metadata `Create`/`Mint` handlers,
+    /// which also create the token record and keep the token account frozen
+    fn mint_programmable(
+        &self,
+        name: String,
+        uri: String,
+        creator: Vec<Creator>,
+        signer_seeds: &[&[&[u8]]],
+    ) -> Result<()> {
+        let token_record = &self
+            .token_record
+            .as_ref()
+            .ok_or(SplNftError::MissingProgrammableAccounts)?
+            .to_account_info();
+        let sysvar_instructions = &self
+            .sysvar_instruction
+            .as_ref()
+            .ok_or(SplNftError::MissingProgrammableAccounts)?
+            .to_account_info();
+
+        // The rule set accounts are only passed when the collection has one
+        let (authorization_rules_program, authorization_rules) =
+            match self.collection_state.rule_set {
+                Some(rule_set) => {
+                    let rules = self
+                        .authorization_rules
+                        .as_ref()
+                        .ok_or(SplNftError::MissingProgrammableAccounts)?;
+                    require_keys_eq!(rules.key(), rule_set, SplNftError::InvalidRuleSet);
+                    let rules_program = self
+                        .authorization_rules_program
+                        .as_ref()
+                        .ok_or(SplNftError::MissingProgrammableAccounts)?;
+                    (
+                        Some(rules_program.to_account_info()),
+                        Some(rules.to_account_info()),
+                    )
+                }
+                None => (None, None),
+            };
+
+        let metadata = &self.metadata.to_account_info();
+        let master_edition = &self.master_edition.to_account_info();
+        let mint = &self.mint.to_account_info();
+        let destination = &self.destination.to_account_info();
+        let owner = &self.owner.to_account_info();
+        let authority = &self.mint_authority.to_account_info();
+        let payer = &self.payer.to_account_info();
+        let system_program = &self.system_program.to_account_info();
+        let spl_token_program = &self.token_program.to_account_info();
+        let spl_ata_program = &self.associated_token_program.to_account_info();
+        let spl_metadata_program = &self.token_metadata_program.to_account_info();
+
+        CreateV1Cpi::new(
+            spl_metadata_program,
+            CreateV1CpiAccounts {
+                metadata,
+                master_edition: Some(master_edition),
+                mint: (mint, false),
+                authority,
+                payer,
+                update_authority: (authority, true),
+                system_program,
+                sysvar_instructions,
+                spl_token_program: Some(spl_token_program),
+            },
+            CreateV1InstructionArgs {
+                name,
+                symbol: self.collection_state.symbol.clone(),
+                uri,
+                seller_fee_basis_points: self.collection_state.seller_fee_basis_points,
+                creators: Some(creator),
+                primary_sale_happened: false,
+                is_mutable: true,
+                token_standard: TokenStandard::ProgrammableNonFungible,
+                collection: Some(Collection {
+                    verified: false,
+                    key: self.collection_mint.key(),
+                }),
+                uses: None,
+                collection_details: None,
+                rule_set: self.collection_state.rule_set,
+                decimals: Some(0),
+                print_supply: Some(PrintSupply::Zero),
+            },
+        )
+        .invoke_signed(signer_seeds)?;
+
+        MintV1Cpi::new(
+            spl_metadata_program,
+            MintV1CpiAccounts {
+                token: destination,
+                token_owner: Some(owner),
+                metadata,
+                master_edition: Some(master_edition),
+                token_record: Some(token_record),
+                mint,
+                authority,
+                delegate_record: None,
+                payer,
+                system_program,
+                sysvar_instructions,
+                spl_token_program,
+                spl_ata_program,
+                authorization_rules_program: authorization_rules_program.as_ref(),
+                authorization_rules: authorization_rules.as_ref(),
+            },
+            MintV1InstructionArgs {
+                amount: 1,
+                authorization_data: None,
+            },
+        )
+        .invoke_signed(signer_seeds)?;
+        msg!("Programmable NFT minted!");
+
+        Ok(())
+    }
+}
+
+/// Append ` #n` to an NFT name, shortening the base so the result fits Metaplex's limit
+fn numbered_name(name: &str, number: u64) -> String {
+    let suffix = format!(" #{}", number);
+    let mut end = name.len().min(MAX_NAME_LEN.saturating_sub(suffix.len()));
+    while !name.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!("{}{}", &name[..end], suffix)
 }
@@ -1,7 +1,15 @@
 pub mod mint_nft;
 pub mod create_collection;
 pub mod verify_collection;
+pub mod update_collection;
+pub mod burn_nft;
+pub mod close_collection;
+pub mod migrate_collection_state;
 
 pub use mint_nft::*;
 pub use create_collection::*;
 pub use verify_collection::*;
+pub use update_collection::*;
+pub use burn_nft::*;
+pub use close_collection::*;
+pub use migrate_collection_state::*;
@@ -0,0 +1,85 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::Mint;
+
+use super::create_collection::{CollectionState, MAX_URI_LEN};
+use crate::errors::SplNftError;
+use crate::events::{CollectionSunset, CollectionUpdated, GatedMinterSet, MintPausedSet};
+
+#[derive(Accounts)]
+pub struct UpdateCollection<'info> {
+    pub authority: Signer<'info>,
+    pub collection_mint: Account<'info, Mint>,
+    #[account(
+        mut,
+        seeds = [b"collection_state", collection_mint.key().as_ref()],
+        bump,
+        has_one = authority,
+    )]
+    pub collection_state: Account<'info, CollectionState>,
+}
+
+impl<'info> UpdateCollection<'info> {
+    /// Change the URI prefix and/or price; `None` leaves a field unchanged.
+    /// Already minted NFTs keep their metadata URI.
+    pub fn update_collection(
+        &mut self,
+        uri_prefix: Option<String>,
+        price: Option<u64>,
+    ) -> Result<()> {
+        if let Some(uri_prefix) = uri_prefix {
+            require!(uri_prefix.len() <= MAX_URI_LEN, SplNftError::UriTooLong);
+            self.collection_state.uri_prefix = uri_prefix;
+        }
+        if let Some(price) = price {
+            self.collection_state.price = price;
+        }
+
+        emit!(CollectionUpdated {
+            collection_mint: self.collection_mint.key(),
+            authority: self.authority.key(),
+            uri_prefix: self.collection_state.uri_prefix.clone(),
+            price: self.collection_state.price,
+        });
+
+        Ok(())
+    }
+
+    /// Pause or resume minting for this collection, including mints via CPI
+    pub fn set_mint_paused(&mut self, paused: bool) -> Result<()> {
+        self.collection_state.mint_paused = paused;
+
+        emit!(MintPausedSet {
+            collection_mint: self.collection_mint.key(),
+            authority: self.authority.key(),
+            paused,
+        });
+
+        Ok(())
+    }
+
+    /// Restrict minting to CPIs from `gated_minter`, or open it to direct calls with `None`
+    pub fn set_gated_minter(&mut self, gated_minter: Option<Pubkey>) -> Result<()> {
+        self.collection_state.gated_minter = gated_minter;
+
+        emit!(GatedMinterSet {
+            collection_mint: self.collection_mint.key(),
+            authority: self.authority.key(),
+            gated_minter,
+        });
+
+        Ok(())
+    }
+
+    /// Retire the collection for good: minting stops and it can be closed
+    pub fn sunset_collection(&mut self) -> Result<()> {
+        self.collection_state.sunset = true;
+
+        emit!(CollectionSunset {
+            collection_mint: self.collection_mint.key(),
+            authority: self.authority.key(),
+            counter: self.collection_state.counter,
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,85 @@
+use anchor_lang::prelude::*;
+
+#[error_code]
+pub enum SplNftError {
+    #[msg("Collection name is too long")]
+    NameTooLong,
+
+    #[msg("Collection symbol is too long")]
+    SymbolTooLong,
+
+    #[msg("URI is too long")]
+    UriTooLong,
+
+    #[msg("Wallet has reached the per-wallet mint limit for this collection")]
+    WalletMintLimitReached,
+
+    #[msg("Collection has reached its maximum supply")]
+    CollectionSoldOut,
+
+    #[msg("Soulbound collections cannot mint programmable NFTs")]
+    SoulboundProgrammableConflict,
+
+    #[msg("Rule set does not match the collection")]
+    InvalidRuleSet,
+
+    #[msg("Programmable NFT accounts are required for this collection")]
+    MissingProgrammableAccounts,
+
+    #[msg("Only the holder or the collection authority can burn this NFT")]
+    UnauthorizedBurn,
+
+    #[msg("NFT does not belong to this collection")]
+    CollectionMismatch,
+
+    #[msg("Name override must start with the collection name")]
+    InvalidNameOverride,
+
+    #[msg("URI override must be under the collection URI prefix")]
+    InvalidUriOverride,
+
+    #[msg("Claim identifier is too long")]
+    ClaimIdentifierTooLong,
+
+    #[msg("Attributes and the attributes account must be passed together")]
+    AttributesAccountMismatch,
+
+    #[msg("Seller fee basis points cannot exceed 10000")]
+    InvalidSellerFee,
+
+    #[msg("Too many creators")]
+    TooManyCreators,
+
+    #[msg("Creators must be distinct and their shares must add up to 100")]
+    InvalidCreators,
+
+    #[msg("Minting is paused for this collection")]
+    MintPaused,
+
+    #[msg("This collection can only be minted through its gated minter program")]
+    UnauthorizedMinter,
+
+    #[msg("Collection has been sunset")]
+    CollectionSunset,
+
+    #[msg("Collection must be empty or sunset before it can be closed")]
+    CollectionStillActive,
+
+    #[msg("Account is not a wallet mint counter of this collection")]
+    InvalidWalletMintCounter,
+
+    #[msg("The holder did not delegate this NFT to the collection authority")]
+    BurnNotDelegated,
+
+    #[msg("Claim attributes can only be set by the collection's gated minter")]
+    AttributesRequireGatedMinter,
+
+    #[msg("Collection state is not in the legacy layout")]
+    InvalidCollectionStateLayout,
+
+    #[msg("Only the holder of the collection NFT can migrate the collection")]
+    UnauthorizedMigration,
+
+    #[msg("Soulbound NFTs must be minted with the owner's signature")]
+    SoulboundMintUnsigned,
+}
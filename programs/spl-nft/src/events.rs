@@ -0,0 +1,66 @@
+use anchor_lang::prelude::*;
+
+/// Emitted when the collection authority changes the URI prefix or price
+#[event]
+pub struct CollectionUpdated {
+    pub collection_mint: Pubkey,
+    pub authority: Pubkey,
+    pub uri_prefix: String,
+    pub price: u64,
+}
+
+/// Emitted when an NFT is burned by its holder or the collection authority
+#[event]
+pub struct NftBurned {
+    pub collection_mint: Pubkey,
+    pub mint: Pubkey,
+    pub holder: Pubkey,
+    pub authority: Pubkey,
+}
+
+/// Emitted by the mint that brings the collection to its maximum supply
+#[event]
+pub struct CollectionSoldOut {
+    pub collection_mint: Pubkey,
+    pub max_supply: u64,
+}
+
+/// Emitted when the collection authority pauses or resumes minting
+#[event]
+pub struct MintPausedSet {
+    pub collection_mint: Pubkey,
+    pub authority: Pubkey,
+    pub paused: bool,
+}
+
+/// Emitted when the collection authority changes the gated minter
+#[event]
+pub struct GatedMinterSet {
+    pub collection_mint: Pubkey,
+    pub authority: Pubkey,
+    pub gated_minter: Option<Pubkey>,
+}
+
+/// Emitted when the collection authority retires the collection
+#[event]
+pub struct CollectionSunset {
+    pub collection_mint: Pubkey,
+    pub authority: Pubkey,
+    pub counter: u64,
+}
+
+/// Emitted when a retired collection's accounts are closed
+#[event]
+pub struct CollectionClosed {
+    pub collection_mint: Pubkey,
+    pub authority: Pubkey,
+    pub closed_wallet_counters: u32,
+}
+
+/// Emitted when a legacy collection state is grown to the current layout
+#[event]
+pub struct CollectionStateMigrated {
+    pub collection_mint: Pubkey,
+    pub authority: Pubkey,
+    pub counter: u64,
+}
@@ -7,6 +7,8 @@ declare_id!("2BrzdsjAbsuvHFcJZswEq6YBNBzuzy2AEXpMR6FLrwck");
 declare_id!("9fH1v7Pa2nUAgd3xbszA1bpSeH8NRL8iQVWuWUuWot3p");
 
 pub mod contexts;
+pub mod errors;
+pub mod events;
 
 pub use contexts::*;
 
@@ -16,21 +18,60 @@ pub mod spl_nft {
     use super::*;
     pub fn create_collection(
         ctx: Context<CreateCollection>,
-        name: String,
-        symbol: String,
-        collection_uri: String,
-        uri_prefix: String,
-        price: u64,
+        config: CollectionConfig,
     ) -> Result<()> {
-        ctx.accounts
-            .create_collection(&ctx.bumps, name, symbol, collection_uri, uri_prefix, price)
+        ctx.accounts.create_collection(&ctx.bumps, config)
     }
 
-    pub fn mint_nft(ctx: Context<MintNFT>) -> Result<()> {
-        ctx.accounts.mint_nft(&ctx.bumps)
+    pub fn mint_nft(
+        ctx: Context<MintNFT>,
+        name: Option<String>,
+        uri: Option<String>,
+        attributes: Option<ClaimAttributes>,
+    ) -> Result<()> {
+        ctx.accounts.mint_nft(&ctx.bumps, name, uri, attributes)
     }
 
     pub fn verify_collection(ctx: Context<VerifyCollectionMint>) -> Result<()> {
         ctx.accounts.verify_collection(&ctx.bumps)
     }
+
+    pub fn update_collection(
+        ctx: Context<UpdateCollection>,
+        uri_prefix: Option<String>,
+        price: Option<u64>,
+    ) -> Result<()> {
+        ctx.accounts.update_collection(uri_prefix, price)
+    }
+
+    pub fn set_mint_paused(ctx: Context<UpdateCollection>, paused: bool) -> Result<()> {
+        ctx.accounts.set_mint_paused(paused)
+    }
+
+    pub fn set_gated_minter(
+        ctx: Context<UpdateCollection>,
+        gated_minter: Option<Pubkey>,
+    ) -> Result<()> {
+        ctx.accounts.set_gated_minter(gated_minter)
+    }
+
+    pub fn burn_nft(ctx: Context<BurnNFT>) -> Result<()> {
+        ctx.accounts.burn_nft(&ctx.bumps)
+    }
+
+    pub fn sunset_collection(ctx: Context<UpdateCollection>) -> Result<()> {
+        ctx.accounts.sunset_collection()
+    }
+
+    /// Move a collection created before collection authorities existed to the current layout
+    pub fn migrate_collection_state(ctx: Context<MigrateCollectionState>) -> Result<()> {
+        ctx.accounts.migrate_collection_state()
+    }
+
+    /// Wallet mint counters to close are passed as remaining_accounts
+    pub fn close_collection<'info>(
+        ctx: Context<'_, '_, '_, 'info, CloseCollection<'info>>,
+    ) -> Result<()> {
+        ctx.accounts.close_collection(ctx.remaining_accounts)
+    }
 }
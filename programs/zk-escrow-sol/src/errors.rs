@@ -49,4 +49,28 @@ pub enum Secp256k1Error {
 
     #[msg("Nullifier has already been used (replay attack prevented)")]
     NullifierAlreadyUsed,
+
+    #[msg("Too many witnesses")]
+    TooManyWitnesses,
+
+    #[msg("Claim owner is not a valid Solana pubkey")]
+    InvalidOwner,
+
+    #[msg("Invalid threshold tiers")]
+    InvalidThresholdTiers,
+
+    #[msg("Expected witness is not in the witness registry")]
+    UnknownWitness,
+
+    #[msg("Verification did not reach the tier required to mint")]
+    TierNotReached,
+
+    #[msg("Account is not in the expected layout")]
+    InvalidAccountLayout,
+
+    #[msg("Account is already in the current layout")]
+    AccountAlreadyMigrated,
+
+    #[msg("Proof context is missing a field the nullifier is derived from")]
+    MissingNullifierFields,
 }
@@ -0,0 +1,71 @@
+use anchor_lang::prelude::*;
+
+/// Emitted when the payment config PDA is created
+#[event]
+pub struct PaymentConfigInitialized {
+    pub authority: Pubkey,
+    pub recipient_bank_account: String,
+    pub allowed_amount: u64,
+    pub fiat_currency: String,
+}
+
+/// Emitted when the witness registry PDA is created
+#[event]
+pub struct WitnessRegistryInitialized {
+    pub authority: Pubkey,
+    pub witness_count: u8,
+    pub required_threshold: u8,
+}
+
+/// Emitted before signatures are checked
+#[event]
+pub struct VerificationStarted {
+    pub claim_identifier: String,
+    pub computed_identifier: String,
+    pub signature_count: u8,
+    pub witness_count: u8,
+    pub required_threshold: u8,
+}
+
+/// Emitted when enough valid witness signatures were recovered
+#[event]
+pub struct VerificationSucceeded {
+    pub claim_identifier: String,
+    pub valid_witness_count: u8,
+    pub required_threshold: u8,
+}
+
+/// Emitted when the threshold was not reached (transaction fails afterwards)
+#[event]
+pub struct VerificationFailed {
+    pub claim_identifier: String,
+    pub valid_witness_count: u8,
+    pub required_threshold: u8,
+}
+
+/// Result of matching the proof context against the payment config
+#[event]
+pub struct PaymentCheckCompleted {
+    pub recipient_matched: bool,
+    pub amount_matched: bool,
+    pub expected_amount: u64,
+}
+
+/// Emitted when a verification result PDA is written
+#[event]
+pub struct VerificationResultStored {
+    pub user: Pubkey,
+    pub verified_at: i64,
+    pub claim_identifier: String,
+    pub tier: u8,
+    pub nullifier: [u8; 32],
+}
+
+/// Emitted after the NFT is minted and its collection verified
+#[event]
+pub struct NftMintedWithProof {
+    pub user: Pubkey,
+    pub mint: Pubkey,
+    pub collection_mint: Pubkey,
+    pub token_id: u64,
+}
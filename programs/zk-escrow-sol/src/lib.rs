@@ -1,5 +1,7 @@
 use anchor_lang::prelude::*;
 pub use anchor_lang::solana_program::sysvar::instructions::ID as INSTRUCTIONS_ID;
+use anchor_lang::solana_program::{instruction::Instruction, program::invoke};
+use anchor_lang::InstructionData;
 use anchor_spl::{
     associated_token::AssociatedToken,
     metadata::{MasterEditionAccount, MetadataAccount},
@@ -8,10 +10,13 @@ use anchor_spl::{
 };
 
 mod errors;
+mod events;
 mod utils;
 
 use errors::*;
-use spl_nft::CollectionState;
+use events::*;
+use nullifier_derive::{derive_nullifier, DEFAULT_NULLIFIER_FIELDS};
+use spl_nft::{ClaimAttributes, CollectionState};
 use utils::*;
 
 #[cfg(feature = "devnet")]
@@ -45,12 +50,15 @@ pub mod zk_escrow_sol {
         config.allowed_amount = allowed_amount;
         config.fiat_currency = fiat_currency.clone();
         config.authority = ctx.accounts.authority.key();
+        config.threshold_tiers = Vec::new();
+        config.mint_tier = 0;
 
-        msg!("ZK Proof Verification program initialized");
-        msg!("Recipient: {}", recipient_bank_account);
-        msg!("Allowed amount: {} KRW", allowed_amount);
-        msg!("Currency: {}", fiat_currency);
-        msg!("Authority: {}", ctx.accounts.authority.key());
+        emit!(PaymentConfigInitialized {
+            authority: config.authority,
+            recipient_bank_account,
+            allowed_amount,
+            fiat_currency,
+        });
 
         Ok(())
     }
@@ -62,7 +70,113 @@ pub mod zk_escrow_sol {
         expected_witnesses: Vec<String>,
         required_threshold: u8,
     ) -> Result<()> {
-        verify_proof_internal_logic(&proof, &expected_witnesses, required_threshold)
+        verify_proof_internal_logic(&proof, &expected_witnesses, required_threshold)?;
+        Ok(())
+    }
+
+    /// Create the shared witness registry PDA
+    /// Downstream programs reference this account instead of storing their own witness lists
+    pub fn initialize_witness_registry(
+        ctx: Context<InitializeWitnessRegistry>,
+        witnesses: Vec<String>,
+        required_threshold: u8,
+    ) -> Result<()> {
+        require!(
+            witnesses.len() <= MAX_WITNESSES,
+            Secp256k1Error::TooManyWitnesses
+        );
+        require!(required_threshold > 0, Secp256k1Error::InvalidThreshold);
+        require!(
+            (required_threshold as usize) <= witnesses.len(),
+            Secp256k1Error::InvalidThreshold
+        );
+
+        let registry = &mut ctx.accounts.witness_registry;
+        registry.authority = ctx.accounts.authority.key();
+        registry.witnesses = witnesses;
+        registry.required_threshold = required_threshold;
+
+        emit!(WitnessRegistryInitialized {
+            authority: registry.authority,
+            witness_count: registry.witnesses.len() as u8,
+            required_threshold,
+        });
+
+        Ok(())
+    }
+
+    /// CPI-friendly proof verification against the witness registry
+    /// Callers pass the registry account instead of duplicating witnesses and threshold
+    pub fn verify_with_registry(ctx: Context<VerifyWithRegistry>, proof: Proof) -> Result<()> {
+        let registry = &ctx.accounts.witness_registry;
+        verify_proof_internal_logic(&proof, &registry.witnesses, registry.required_threshold)?;
+        Ok(())
+    }
+
+    /// Configure named threshold tiers (e.g. "mint" = 2, "withdraw" = 4)
+    /// Tiers must be ordered by strictly increasing threshold
+    /// `mint_tier` is the tier a verification must reach before it can mint (0 = any)
+    pub fn set_threshold_tiers(
+        ctx: Context<UpdatePaymentConfig>,
+        threshold_tiers: Vec<ThresholdTier>,
+        mint_tier: u8,
+    ) -> Result<()> {
+        validate_threshold_tiers(&threshold_tiers)?;
+        require!(
+            (mint_tier as usize) <= threshold_tiers.len(),
+            Secp256k1Error::InvalidThresholdTiers
+        );
+
+        let config = &mut ctx.accounts.payment_config;
+        config.threshold_tiers = threshold_tiers;
+        config.mint_tier = mint_tier;
+
+        Ok(())
+    }
+
+    /// Grow a payment config created before threshold tiers existed to the current layout
+    /// New fields read as empty/zero; the authority funds the extra rent
+    pub fn migrate_payment_config(ctx: Context<MigratePaymentConfig>) -> Result<()> {
+        let config_info = ctx.accounts.payment_config.to_account_info();
+        {
+            let data = config_info.try_borrow_data()?;
+            require!(
+                data.len() >= 8 + 32 && data[..8] == *PaymentConfig::DISCRIMINATOR,
+                Secp256k1Error::InvalidAccountLayout
+            );
+            require!(
+                data[8..8 + 32] == ctx.accounts.authority.key().to_bytes(),
+                Secp256k1Error::UnauthorizedUser
+            );
+        }
+
+        grow_account(
+            &config_info,
+            &ctx.accounts.authority.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            8 + PaymentConfig::INIT_SPACE,
+        )
+    }
+
+    /// Grow a verification result written before witness counts, tiers and nullifiers were
+    /// recorded. Permissionless; the old result reads as tier 0 with a zero nullifier until
+    /// the user verifies again
+    pub fn migrate_verification_result(ctx: Context<MigrateVerificationResult>) -> Result<()> {
+        let result_info = ctx.accounts.verification_result.to_account_info();
+        {
+            let data = result_info.try_borrow_data()?;
+            require!(
+                data.len() >= 8 && data[..8] == *VerificationResult::DISCRIMINATOR,
+                Secp256k1Error::InvalidAccountLayout
+            );
+        }
+
+        grow_account(
+            &result_info,
+            &ctx.accounts.payer.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            8 + VerificationResult::INIT_SPACE,
+        )
     }
 
     /// Two-Transaction Pattern: Step 1 - Verify proof and store result in PDA
@@ -74,8 +188,6 @@ pub mod zk_escrow_sol {
         expected_witnesses: Vec<String>,
         required_threshold: u8,
     ) -> Result<()> {
-        msg!("=== Step 1: Verify Proof ===");
-
         // 1. Verify payment details from stored config
         let config = &ctx.accounts.payment_config;
         verify_payment_details_from_context(
@@ -85,20 +197,47 @@ pub mod zk_escrow_sol {
             &config.fiat_currency,
         )?;
 
-        // 2. Verify proof signatures using internal logic
-        verify_proof_internal_logic(&proof, &expected_witnesses, required_threshold)?;
+        // 2. Only registry witnesses may count towards the threshold and the tier,
+        // and callers cannot go below the registry threshold
+        let witness_registry = &ctx.accounts.witness_registry;
+        require!(
+            expected_witnesses
+                .iter()
+                .all(|witness| witness_registry.contains(witness)),
+            Secp256k1Error::UnknownWitness
+        );
+        require!(
+            required_threshold >= witness_registry.required_threshold,
+            Secp256k1Error::InvalidThreshold
+        );
 
-        // 3. Store verification result in PDA
+        // 3. Verify proof signatures using internal logic
+        let valid_witness_count =
+            verify_proof_internal_logic(&proof, &expected_witnesses, required_threshold)?;
+        let tier = reached_tier(&config.threshold_tiers, valid_witness_count);
+
+        // 4. Derive the payment's nullifier the same way as nullifier-registry and clients
+        let nullifier = derive_nullifier(&proof.claim_info.context, &DEFAULT_NULLIFIER_FIELDS)
+            .ok_or(Secp256k1Error::MissingNullifierFields)?;
+
+        // 5. Store verification result in PDA
         let result = &mut ctx.accounts.verification_result;
         result.user = ctx.accounts.signer.key();
         result.verified_at = Clock::get()?.unix_timestamp;
         result.claim_identifier = proof.signed_claim.claim.identifier.clone();
         result.is_used = false;
-
-        msg!("Verification result stored in PDA");
-        msg!("User: {}", result.user);
-        msg!("Verified at: {}", result.verified_at);
-        msg!("Claim ID: {}", result.claim_identifier);
+        result.valid_witness_count = valid_witness_count;
+        result.tier = tier;
+        result.payment_amount = config.allowed_amount;
+        result.nullifier = nullifier;
+
+        emit!(VerificationResultStored {
+            user: result.user,
+            verified_at: result.verified_at,
+            claim_identifier: result.claim_identifier.clone(),
+            tier,
+            nullifier,
+        });
 
         Ok(())
     }
@@ -106,9 +245,12 @@ pub mod zk_escrow_sol {
     /// Two-Transaction Pattern: Step 2 - Mint NFT using verified proof result
     /// This transaction is small because it only checks PDA (no large proof data)
     /// The verification result PDA is reusable - can verify new proof and mint again
-    pub fn mint_with_verified_proof(ctx: Context<MintWithVerifiedProof>) -> Result<()> {
-        msg!("=== Step 2: Mint NFT with Verified Proof ===");
-
+    /// Optional name/uri override the collection defaults (validated by spl_nft)
+    pub fn mint_with_verified_proof(
+        ctx: Context<MintWithVerifiedProof>,
+        name: Option<String>,
+        uri: Option<String>,
+    ) -> Result<()> {
         let result = &ctx.accounts.verification_result;
 
         // 1. Security checks
@@ -118,6 +260,12 @@ pub mod zk_escrow_sol {
             Secp256k1Error::UnauthorizedUser
         );
 
+        // Verify the proof reached the tier required for minting
+        require!(
+            result.meets_tier(ctx.accounts.payment_config.mint_tier),
+            Secp256k1Error::TierNotReached
+        );
+
         // Verify destination is the correct ATA for (verified user, mint)
         let expected_destination = anchor_spl::associated_token::get_associated_token_address(
             &result.user.key(),
@@ -128,18 +276,16 @@ pub mod zk_escrow_sol {
             Secp256k1Error::UnauthorizedUser
         );
 
-        msg!("NFT recipient and destination verified: {}", result.user);
-
-        // 2. Get collection info for logging
-        let collection_state = &ctx.accounts.collection_state;
-        msg!("Collection: {}", collection_state.name);
-        msg!("Price: {} KRW", collection_state.price);
-        msg!("Counter: {}", collection_state.counter);
+        let user = result.user;
 
-        // 3. Mint NFT via CPI
+        // 2. Mint NFT via CPI
         // owner = verified user (receives NFT), payer = signer (pays for accounts)
         // spl_nft will create destination ATA with authority=owner
 
+        // spl_nft only takes claim attributes from a collection's gated minter, so
+        // collections not gated to this program get the NFT without them
+        let with_attributes = ctx.accounts.collection_state.gated_minter == Some(crate::ID);
+
         let cpi_program = ctx.accounts.spl_nft_program.to_account_info();
         let cpi_accounts = spl_nft::cpi::accounts::MintNFT {
             owner: ctx.accounts.nft_recipient.to_account_info(),
@@ -151,25 +297,63 @@ pub mod zk_escrow_sol {
             mint_authority: ctx.accounts.mint_authority.to_account_info(),
             collection_mint: ctx.accounts.collection_mint.to_account_info(),
             collection_state: ctx.accounts.collection_state.to_account_info(),
+            wallet_mint_counter: ctx.accounts.wallet_mint_counter.to_account_info(),
+            mint_record: ctx.accounts.mint_record.to_account_info(),
             system_program: ctx.accounts.system_program.to_account_info(),
             token_program: ctx.accounts.token_program.to_account_info(),
             associated_token_program: ctx.accounts.associated_token_program.to_account_info(),
             token_metadata_program: ctx.accounts.token_metadata_program.to_account_info(),
+            token_record: ctx
+                .accounts
+                .token_record
+                .as_ref()
+                .map(|account| account.to_account_info()),
+            sysvar_instruction: Some(ctx.accounts.sysvar_instruction.to_account_info()),
+            authorization_rules_program: ctx
+                .accounts
+                .authorization_rules_program
+                .as_ref()
+                .map(|account| account.to_account_info()),
+            authorization_rules: ctx
+                .accounts
+                .authorization_rules
+                .as_ref()
+                .map(|account| account.to_account_info()),
+            nft_attributes: with_attributes.then(|| ctx.accounts.nft_attributes.to_account_info()),
         };
 
-        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
-        spl_nft::cpi::mint_nft(cpi_ctx)?;
-
-        msg!("NFT minted successfully!");
-        msg!(
-            "URI: {}/{}",
-            collection_state.uri_prefix,
-            collection_state.counter
-        );
-
-        // 4. Verify collection (mark NFT as verified)
-        msg!("=== Step 3: Verify Collection ===");
+        // Record what the NFT attests to alongside it
+        let attributes = with_attributes.then(|| ClaimAttributes {
+            claim_identifier: result.claim_identifier.clone(),
+            amount_bucket: amount_bucket(result.payment_amount),
+            verified_at: result.verified_at,
+        });
+
+        // Built by hand rather than through spl_nft::cpi so a co-signing recipient stays a
+        // signer: soulbound mints need the owner's signature to delegate the NFT
+        let mut account_metas = cpi_accounts.to_account_metas(None);
+        let recipient = ctx.accounts.nft_recipient.key();
+        for meta in account_metas
+            .iter_mut()
+            .filter(|meta| meta.pubkey == recipient)
+        {
+            meta.is_signer |= ctx.accounts.nft_recipient.is_signer;
+        }
+        let mint_ix = Instruction {
+            program_id: cpi_program.key(),
+            accounts: account_metas,
+            data: spl_nft::instruction::MintNft {
+                name,
+                uri,
+                attributes,
+            }
+            .data(),
+        };
+        let mut account_infos = cpi_accounts.to_account_infos();
+        account_infos.push(cpi_program);
+        invoke(&mint_ix, &account_infos)?;
 
+        // 3. Verify collection (mark NFT as verified)
         let verify_cpi_program = ctx.accounts.spl_nft_program.to_account_info();
         let verify_cpi_accounts = spl_nft::cpi::accounts::VerifyCollectionMint {
             authority: ctx.accounts.signer.to_account_info(),
@@ -187,7 +371,14 @@ pub mod zk_escrow_sol {
         let verify_cpi_ctx = CpiContext::new(verify_cpi_program, verify_cpi_accounts);
         spl_nft::cpi::verify_collection(verify_cpi_ctx)?;
 
-        msg!("Collection verified! NFT is now marked as verified: true");
+        // Counter was incremented by spl_nft during the mint CPI
+        ctx.accounts.collection_state.reload()?;
+        emit!(NftMintedWithProof {
+            user,
+            mint: ctx.accounts.mint.key(),
+            collection_mint: ctx.accounts.collection_mint.key(),
+            token_id: ctx.accounts.collection_state.counter,
+        });
 
         // Note: verification_result PDA remains open and can be reused
         // User can verify a new proof and mint another NFT using the same PDA
@@ -198,15 +389,12 @@ pub mod zk_escrow_sol {
 
 /// Internal helper function for proof verification logic
 /// Called by both verify_proof_signatures and verify_proof_internal
+/// Returns the number of distinct valid witness signatures
 fn verify_proof_internal_logic(
     proof: &Proof,
     expected_witnesses: &Vec<String>,
     required_threshold: u8,
-) -> Result<()> {
-    msg!("=== Starting Proof Verification ===");
-    msg!("Required threshold: {}", required_threshold);
-    msg!("Expected witnesses: {:?}", expected_witnesses);
-
+) -> Result<u8> {
     // 1. Verify required_threshold is valid
     require!(required_threshold > 0, Secp256k1Error::InvalidThreshold);
     require!(
@@ -226,11 +414,13 @@ fn verify_proof_internal_logic(
     );
     let computed_identifier_str = format!("0x{}", hex::encode(computed_identifier));
 
-    msg!("Computed identifier: {}", computed_identifier_str);
-    msg!(
-        "Expected identifier: {}",
-        proof.signed_claim.claim.identifier
-    );
+    emit!(VerificationStarted {
+        claim_identifier: proof.signed_claim.claim.identifier.clone(),
+        computed_identifier: computed_identifier_str.clone(),
+        signature_count: proof.signed_claim.signatures.len() as u8,
+        witness_count: expected_witnesses.len() as u8,
+        required_threshold,
+    });
 
     // require!(
     //     computed_identifier_str.eq_ignore_ascii_case(&proof.signed_claim.claim.identifier),
@@ -241,11 +431,10 @@ fn verify_proof_internal_logic(
     let claim_message = serialise_claim_data(
         &proof.signed_claim.claim.identifier,
         &proof.signed_claim.claim.owner,
+        proof.signed_claim.claim.owner_format,
         proof.signed_claim.claim.timestamp_s,
         proof.signed_claim.claim.epoch,
-    );
-
-    msg!("Claim message: {}", claim_message);
+    )?;
 
     let message_hash = hash_ethereum_message(&claim_message);
 
@@ -253,12 +442,9 @@ fn verify_proof_internal_logic(
     let mut valid_witness_count: u8 = 0;
     let mut seen_witnesses: Vec<String> = Vec::new();
 
-    for (i, signature) in proof.signed_claim.signatures.iter().enumerate() {
-        msg!("Processing signature {}", i);
-
-        // Validate signature format
+    for signature in proof.signed_claim.signatures.iter() {
+        // Skip signatures with an invalid format
         if signature.len() != 65 {
-            msg!("Signature {} has invalid length, skipping", i);
             continue;
         }
 
@@ -268,25 +454,15 @@ fn verify_proof_internal_logic(
         // Recover signer address
         let recovered_address = match recover_signer_address(&message_hash, &sig_array) {
             Ok(addr) => addr,
-            Err(_) => {
-                msg!("Failed to recover address from signature {}, skipping", i);
-                continue;
-            }
+            Err(_) => continue,
         };
 
-        msg!(
-            "Recovered address from signature {}: {}",
-            i,
-            recovered_address
-        );
-
         // Check if this witness was already counted (prevent duplicate counting)
         let already_seen = seen_witnesses
             .iter()
             .any(|w| w.eq_ignore_ascii_case(&recovered_address));
 
         if already_seen {
-            msg!("Witness {} already counted, skipping", recovered_address);
             continue;
         }
 
@@ -296,25 +472,103 @@ fn verify_proof_internal_logic(
             .any(|w| w.eq_ignore_ascii_case(&recovered_address));
 
         if is_valid_witness {
-            msg!("Valid witness found: {}", recovered_address);
             seen_witnesses.push(recovered_address);
             valid_witness_count += 1;
-        } else {
-            msg!(
-                "Recovered address {} is not an expected witness",
-                recovered_address
-            );
         }
     }
 
     // 5. Check if we have enough valid witness signatures
+    if valid_witness_count < required_threshold {
+        emit!(VerificationFailed {
+            claim_identifier: proof.signed_claim.claim.identifier.clone(),
+            valid_witness_count,
+            required_threshold,
+        });
+    }
     require!(
         valid_witness_count >= required_threshold,
         Secp256k1Error::AddressMismatch
     );
+
+    emit!(VerificationSucceeded {
+        claim_identifier: proof.signed_claim.claim.identifier.clone(),
+        valid_witness_count,
+        required_threshold,
+    });
+    Ok(valid_witness_count)
+}
+
+/// Validate threshold tiers: bounded count, non-empty names, strictly increasing thresholds
+fn validate_threshold_tiers(tiers: &[ThresholdTier]) -> Result<()> {
+    require!(
+        tiers.len() <= MAX_THRESHOLD_TIERS,
+        Secp256k1Error::InvalidThresholdTiers
+    );
+
+    let mut previous_threshold = 0u8;
+    for tier in tiers {
+        require!(
+            !tier.name.is_empty() && tier.name.len() <= MAX_TIER_NAME_LEN,
+            Secp256k1Error::InvalidThresholdTiers
+        );
+        require!(
+            tier.threshold > previous_threshold,
+            Secp256k1Error::InvalidThresholdTiers
+        );
+        previous_threshold = tier.threshold;
+    }
+
+    Ok(())
+}
+
+/// Grow a program-owned account to `space` bytes, topping up rent from `payer`
+/// The added bytes are zeroed, so appended fields deserialize as empty/zero
+fn grow_account<'info>(
+    info: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    space: usize,
+) -> Result<()> {
+    require_keys_eq!(*info.owner, crate::ID, Secp256k1Error::InvalidAccountLayout);
+    require!(
+        info.data_len() < space,
+        Secp256k1Error::AccountAlreadyMigrated
+    );
+
+    let top_up = Rent::get()?
+        .minimum_balance(space)
+        .saturating_sub(info.lamports());
+    if top_up > 0 {
+        anchor_lang::system_program::transfer(
+            CpiContext::new(
+                system_program.clone(),
+                anchor_lang::system_program::Transfer {
+                    from: payer.clone(),
+                    to: info.clone(),
+                },
+            ),
+            top_up,
+        )?;
+    }
+    info.resize(space)?;
+
     Ok(())
 }
 
+/// Number of configured tiers reached by the given witness count
+/// (0 = no tier reached, n = tiers[0..n] are satisfied)
+fn reached_tier(tiers: &[ThresholdTier], valid_witness_count: u8) -> u8 {
+    tiers
+        .iter()
+        .take_while(|tier| valid_witness_count >= tier.threshold)
+        .count() as u8
+}
+
+/// Bucket a payment amount by its number of decimal digits (e.g., 1000 -> 4)
+fn amount_bucket(amount: u64) -> u8 {
+    amount.checked_ilog10().map_or(0, |digits| digits as u8 + 1)
+}
+
 /// Format number with comma separator (e.g., 1000 -> "-1,000")
 fn format_number_with_comma(amount: u64) -> String {
     let amount_str = amount.to_string();
@@ -340,9 +594,6 @@ fn verify_payment_details_from_context(
     expected_amount: u64,
     expected_currency: &str,
 ) -> Result<()> {
-    msg!("=== Verifying Payment Details ===");
-    msg!("Context: {}", context);
-
     // Validation constraints
     require!(
         !expected_recipient.is_empty(),
@@ -359,8 +610,6 @@ fn verify_payment_details_from_context(
 
     // Check recipient bank account
     let recipient_found = context.contains(expected_recipient);
-    require!(recipient_found, Secp256k1Error::RecipientMismatch);
-    msg!("✓ Recipient bank account verified: {}", expected_recipient);
 
     // Check amount (match raw format from context: e.g., "-1000" or "-1,000")
     // Context contains negative amounts like "transactionAmount":"-1,000" (with comma)
@@ -371,16 +620,16 @@ fn verify_payment_details_from_context(
     let amount_found = context.contains(&formatted_amount_no_comma)
         || context.contains(&formatted_amount_with_comma);
 
+    emit!(PaymentCheckCompleted {
+        recipient_matched: recipient_found,
+        amount_matched: amount_found,
+        expected_amount,
+    });
+
+    require!(recipient_found, Secp256k1Error::RecipientMismatch);
     require!(amount_found, Secp256k1Error::AmountMismatch);
-    msg!(
-        "✓ Payment amount verified: {} KRW (accepting both comma and no-comma formats)",
-        expected_amount
-    );
 
     // Currency is already validated above (must be KRW)
-    msg!("✓ Currency verified: {}", expected_currency);
-
-    msg!("Payment details verification successful!");
     Ok(())
 }
 
@@ -401,11 +650,78 @@ pub struct Initialize<'info> {
     pub system_program: Program<'info, System>,
 }
 
+#[derive(Accounts)]
+pub struct UpdatePaymentConfig<'info> {
+    #[account(
+        mut,
+        seeds = [b"payment_config"],
+        bump,
+        has_one = authority,
+    )]
+    pub payment_config: Account<'info, PaymentConfig>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct MigratePaymentConfig<'info> {
+    /// CHECK: Pre-tier layout; owner, discriminator and authority are checked in the handler
+    #[account(
+        mut,
+        seeds = [b"payment_config"],
+        bump,
+    )]
+    pub payment_config: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateVerificationResult<'info> {
+    /// CHECK: Pre-tier layout; owner and discriminator are checked in the handler
+    #[account(mut)]
+    pub verification_result: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
 #[derive(Accounts)]
 pub struct VerifyProofInternal<'info> {
     pub signer: Signer<'info>,
 }
 
+#[derive(Accounts)]
+pub struct InitializeWitnessRegistry<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + WitnessRegistry::INIT_SPACE,
+        seeds = [b"witness_registry"],
+        bump,
+    )]
+    pub witness_registry: Account<'info, WitnessRegistry>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct VerifyWithRegistry<'info> {
+    #[account(
+        seeds = [b"witness_registry"],
+        bump,
+    )]
+    pub witness_registry: Account<'info, WitnessRegistry>,
+}
+
 // ============================================================================
 // Data Structures (zk-escrow compatible)
 // ============================================================================
@@ -420,6 +736,46 @@ pub struct PaymentConfig {
     pub allowed_amount: u64,
     #[max_len(10)]
     pub fiat_currency: String,
+    #[max_len(MAX_THRESHOLD_TIERS)]
+    pub threshold_tiers: Vec<ThresholdTier>,
+    /// Tier a verification must reach before `mint_with_verified_proof` accepts it (0 = any)
+    pub mint_tier: u8,
+}
+
+/// Maximum number of named threshold tiers in the payment config
+pub const MAX_THRESHOLD_TIERS: usize = 4;
+
+/// Maximum length of a threshold tier name
+pub const MAX_TIER_NAME_LEN: usize = 16;
+
+/// Named witness threshold (e.g. "mint" = 2-of-5, "withdraw" = 4-of-5)
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, InitSpace)]
+pub struct ThresholdTier {
+    #[max_len(MAX_TIER_NAME_LEN)]
+    pub name: String,
+    pub threshold: u8,
+}
+
+/// Maximum number of witnesses stored in the registry
+pub const MAX_WITNESSES: usize = 10;
+
+/// Shared witness set and threshold used for proof verification
+#[account]
+#[derive(InitSpace)]
+pub struct WitnessRegistry {
+    pub authority: Pubkey,
+    #[max_len(MAX_WITNESSES, 42)] // 0x + 40 hex chars
+    pub witnesses: Vec<String>,
+    pub required_threshold: u8,
+}
+
+impl WitnessRegistry {
+    /// Whether `witness` is a registered witness address (case-insensitive hex)
+    pub fn contains(&self, witness: &str) -> bool {
+        self.witnesses
+            .iter()
+            .any(|registered| registered.eq_ignore_ascii_case(witness))
+    }
 }
 
 /// Claim information containing provider, parameters, and context
@@ -430,11 +786,21 @@ pub struct ClaimInfo {
     pub context: String,
 }
 
+/// Encoding of the claim owner
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OwnerFormat {
+    /// Hex Ethereum address (case-insensitive, normalised to lowercase)
+    Ethereum,
+    /// Base58 Solana pubkey (case-sensitive, serialised verbatim)
+    Solana,
+}
+
 /// Complete claim data with identifier, owner, timestamp, and epoch
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
 pub struct ClaimDataInput {
     pub identifier: String,
     pub owner: String,
+    pub owner_format: OwnerFormat,
     pub timestamp_s: u32,
     pub epoch: u32,
 }
@@ -474,6 +840,26 @@ pub struct VerificationResult {
 
     /// Whether this verification has been used for minting
     pub is_used: bool,
+
+    /// Number of distinct valid witness signatures in the proof
+    pub valid_witness_count: u8,
+
+    /// Highest threshold tier reached (0 = none, n = first n tiers of the payment config)
+    pub tier: u8,
+
+    /// Payment amount matched by the proof
+    pub payment_amount: u64,
+
+    /// Nullifier of the verified payment (see `nullifier-derive`), to be marked in the
+    /// nullifier registry so the same payment cannot be claimed twice
+    pub nullifier: [u8; 32],
+}
+
+impl VerificationResult {
+    /// Whether the verification reached the given tier (1-based index into threshold_tiers)
+    pub fn meets_tier(&self, tier: u8) -> bool {
+        self.tier >= tier
+    }
 }
 
 /// Account structure for verify_proof instruction
@@ -497,6 +883,13 @@ pub struct VerifyProof<'info> {
     )]
     pub payment_config: Account<'info, PaymentConfig>,
 
+    /// Witness set the proof is counted against
+    #[account(
+        seeds = [b"witness_registry"],
+        bump,
+    )]
+    pub witness_registry: Account<'info, WitnessRegistry>,
+
     pub system_program: Program<'info, System>,
 }
 
@@ -511,7 +904,14 @@ pub struct MintWithVerifiedProof<'info> {
     #[account(mut)]
     pub verification_result: Account<'info, VerificationResult>,
 
-    /// The verified user who will receive the NFT
+    /// Payment config holding the tier required to mint
+    #[account(
+        seeds = [b"payment_config"],
+        bump,
+    )]
+    pub payment_config: Account<'info, PaymentConfig>,
+
+    /// The verified user who will receive the NFT; must co-sign for soulbound collections
     /// CHECK: This account is validated against verification_result.user
     pub nft_recipient: UncheckedAccount<'info>,
 
@@ -549,6 +949,30 @@ pub struct MintWithVerifiedProof<'info> {
     )]
     pub collection_state: Account<'info, CollectionState>,
 
+    /// Per-wallet mint counter (created by spl_nft on first mint)
+    /// CHECK: Validated and initialized by spl_nft program
+    #[account(
+        mut,
+        seeds = [
+            b"wallet_mints",
+            collection_mint.key().as_ref(),
+            nft_recipient.key().as_ref(),
+        ],
+        bump,
+        seeds::program = spl_nft_program.key(),
+    )]
+    pub wallet_mint_counter: UncheckedAccount<'info>,
+
+    /// Per-mint edition record (created by spl_nft)
+    /// CHECK: Validated and initialized by spl_nft program
+    #[account(
+        mut,
+        seeds = [b"mint_record", mint.key().as_ref()],
+        bump,
+        seeds::program = spl_nft_program.key(),
+    )]
+    pub mint_record: UncheckedAccount<'info>,
+
     // ========== Verify Collection Accounts ==========
     /// Collection metadata (Metaplex)
     #[account(mut)]
@@ -570,4 +994,94 @@ pub struct MintWithVerifiedProof<'info> {
 
     /// CHECK: Token Metadata Program
     pub token_metadata_program: UncheckedAccount<'info>,
+
+    // ========== Programmable NFT Accounts (pNFT collections only) ==========
+    /// CHECK: Token record PDA, created by Metaplex during the mint CPI
+    #[account(mut)]
+    pub token_record: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Validated by Metaplex
+    pub authorization_rules_program: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Validated against the collection rule set by spl_nft
+    pub authorization_rules: Option<UncheckedAccount<'info>>,
+
+    /// Claim attributes companion account (created by spl_nft when the collection is gated
+    /// to this program)
+    /// CHECK: Validated and initialized by spl_nft program
+    #[account(
+        mut,
+        seeds = [b"attributes", mint.key().as_ref()],
+        bump,
+        seeds::program = spl_nft_program.key(),
+    )]
+    pub nft_attributes: UncheckedAccount<'info>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tiers() -> Vec<ThresholdTier> {
+        vec![
+            ThresholdTier {
+                name: "mint".to_string(),
+                threshold: 2,
+            },
+            ThresholdTier {
+                name: "withdraw".to_string(),
+                threshold: 4,
+            },
+        ]
+    }
+
+    fn result_with_tier(tier: u8) -> VerificationResult {
+        VerificationResult {
+            user: Pubkey::default(),
+            verified_at: 0,
+            claim_identifier: String::new(),
+            is_used: false,
+            valid_witness_count: 0,
+            tier,
+            payment_amount: 0,
+            nullifier: [0u8; 32],
+        }
+    }
+
+    #[test]
+    fn reached_tier_counts_satisfied_tiers() {
+        assert_eq!(reached_tier(&tiers(), 1), 0);
+        assert_eq!(reached_tier(&tiers(), 2), 1);
+        assert_eq!(reached_tier(&tiers(), 3), 1);
+        assert_eq!(reached_tier(&tiers(), 5), 2);
+        assert_eq!(reached_tier(&[], 5), 0);
+    }
+
+    #[test]
+    fn meets_tier_gates_on_reached_tier() {
+        assert!(result_with_tier(0).meets_tier(0));
+        assert!(!result_with_tier(0).meets_tier(1));
+        assert!(result_with_tier(2).meets_tier(1));
+        assert!(!result_with_tier(1).meets_tier(2));
+    }
+
+    #[test]
+    fn validate_threshold_tiers_requires_increasing_thresholds() {
+        assert!(validate_threshold_tiers(&tiers()).is_ok());
+
+        let mut unordered = tiers();
+        unordered.reverse();
+        assert!(validate_threshold_tiers(&unordered).is_err());
+    }
+
+    #[test]
+    fn witness_registry_matches_case_insensitively() {
+        let registry = WitnessRegistry {
+            authority: Pubkey::default(),
+            witnesses: vec!["0xAbCd".to_string()],
+            required_threshold: 1,
+        };
+        assert!(registry.contains("0xabcd"));
+        assert!(!registry.contains("0xef01"));
+    }
 }
@@ -1,7 +1,9 @@
 use anchor_lang::prelude::*;
 use anchor_lang::solana_program::keccak::hash as keccak_256;
+use std::str::FromStr;
 
 use crate::errors::Secp256k1Error;
+use crate::OwnerFormat;
 
 /// Compute the claim identifier by hashing provider, parameters and context
 /// with newline separators, matching Solidity Claims.hashClaimInfo
@@ -18,17 +20,36 @@ pub fn hash_claim_info(provider: &str, parameters: &str, context: &str) -> [u8;
     keccak_256(serialized.as_bytes()).to_bytes()
 }
 
+/// Normalise the claim owner according to its format.
+/// Ethereum addresses are lowercased; Solana pubkeys are case-sensitive Base58
+/// so they are validated as 32-byte keys and kept verbatim.
+pub fn normalise_owner(owner: &str, owner_format: OwnerFormat) -> Result<String> {
+    match owner_format {
+        OwnerFormat::Ethereum => Ok(owner.to_lowercase()),
+        OwnerFormat::Solana => {
+            Pubkey::from_str(owner).map_err(|_| Secp256k1Error::InvalidOwner)?;
+            Ok(owner.to_string())
+        }
+    }
+}
+
 /// Serialise claim data (identifier, owner, timestamp, epoch) exactly like
 /// Solidity Claims.serialise which is used to create the signed payload.
-pub fn serialise_claim_data(identifier: &str, owner: &str, timestamp_s: u32, epoch: u32) -> String {
+pub fn serialise_claim_data(
+    identifier: &str,
+    owner: &str,
+    owner_format: OwnerFormat,
+    timestamp_s: u32,
+    epoch: u32,
+) -> Result<String> {
     // The Solidity helper normalises to lowercase hex with 0x prefix.
     let identifier_normalised = identifier.to_lowercase();
-    let owner_normalised = owner.to_lowercase();
+    let owner_normalised = normalise_owner(owner, owner_format)?;
 
-    format!(
+    Ok(format!(
         "{}\n{}\n{}\n{}",
         identifier_normalised, owner_normalised, timestamp_s, epoch
-    )
+    ))
 }
 
 #[cfg(test)]
@@ -54,4 +75,39 @@ mod tests {
         let modified = hash_claim_info(PROVIDER, PARAMETERS, modified_context);
         assert_ne!(original, modified);
     }
+
+    #[test]
+    fn serialise_claim_data_lowercases_ethereum_owner() {
+        let message = serialise_claim_data(
+            "0xABCD",
+            "0xF2F5AC9AE67A6F5C8E0D1E3B3F0D1A1A1A1A1A1A",
+            OwnerFormat::Ethereum,
+            1,
+            1,
+        )
+        .unwrap();
+        assert_eq!(
+            message,
+            "0xabcd\n0xf2f5ac9ae67a6f5c8e0d1e3b3f0d1a1a1a1a1a1a\n1\n1"
+        );
+    }
+
+    #[test]
+    fn serialise_claim_data_keeps_solana_owner_verbatim() {
+        let owner = "3EgB44qFrL2gZhi24oiw6kqV6YTdg1i8hdG9nNxHLUzK";
+        let message = serialise_claim_data("0xABCD", owner, OwnerFormat::Solana, 1, 1).unwrap();
+        assert_eq!(message, format!("0xabcd\n{}\n1\n1", owner));
+    }
+
+    #[test]
+    fn serialise_claim_data_rejects_invalid_solana_owner() {
+        let result = serialise_claim_data(
+            "0xabcd",
+            "0xf2f5ac9ae67a6f5c8e0d1e3b3f0d1a1a1a1a1a1a",
+            OwnerFormat::Solana,
+            1,
+            1,
+        );
+        assert!(result.is_err());
+    }
 }
@@ -1,13 +1,19 @@
 import * as anchor from '@coral-xyz/anchor'
 import { Keypair, PublicKey, SystemProgram } from '@solana/web3.js'
 import { getAssociatedTokenAddressSync } from '@solana/spl-token'
-import { getSplNftProgram } from '../tests/utils'
+import { getProgram, getSplNftProgram } from '../tests/utils'
 import {
   COLLECTION_NAME,
   COLLECTION_SYMBOL,
   COLLECTION_URI,
   COLLECTION_URI_PREFIX,
   NFT_PRICE,
+  MAX_MINTS_PER_WALLET,
+  MAX_SUPPLY,
+  SOULBOUND,
+  PROGRAMMABLE,
+  SELLER_FEE_BASIS_POINTS,
+  GATE_MINTS_TO_ZK_ESCROW,
 } from '../constants'
 
 // Devnet
@@ -173,18 +179,35 @@ const COLLECTION_ADDRESSES = {
   console.log('  Symbol:', COLLECTION_SYMBOL)
   console.log('  URI Prefix:', COLLECTION_URI)
   console.log('  Price:', NFT_PRICE, 'KRW')
+  console.log('  Max mints per wallet:', MAX_MINTS_PER_WALLET)
+  console.log('  Max supply:', MAX_SUPPLY)
+  console.log('  Soulbound:', SOULBOUND)
+  console.log('  Programmable:', PROGRAMMABLE)
+  console.log('  Seller fee (bps):', SELLER_FEE_BASIS_POINTS)
+
+  // Only the zk-escrow program may mint (after proof verification)
+  const gatedMinter = GATE_MINTS_TO_ZK_ESCROW ? getProgram().programId : null
+  console.log('  Gated minter:', gatedMinter?.toBase58() ?? 'none')
 
   console.log('\n📝 Creating collection transaction...')
 
   // Create collection
   const tx = await splNftProgram.methods
-    .createCollection(
-      COLLECTION_NAME,
-      COLLECTION_SYMBOL,
-      COLLECTION_URI,
-      COLLECTION_URI_PREFIX,
-      new anchor.BN(NFT_PRICE),
-    )
+    .createCollection({
+      name: COLLECTION_NAME,
+      symbol: COLLECTION_SYMBOL,
+      collectionUri: COLLECTION_URI,
+      uriPrefix: COLLECTION_URI_PREFIX,
+      price: new anchor.BN(NFT_PRICE),
+      maxMintsPerWallet: MAX_MINTS_PER_WALLET,
+      maxSupply: new anchor.BN(MAX_SUPPLY),
+      soulbound: SOULBOUND,
+      programmable: PROGRAMMABLE,
+      ruleSet: null,
+      sellerFeeBasisPoints: SELLER_FEE_BASIS_POINTS,
+      creators: [], // mint authority only
+      gatedMinter,
+    })
     .accounts({
       user: deployer.publicKey,
       mint: collectionMint,
@@ -90,6 +90,7 @@ async function main() {
 
   // Verify collection exists
   console.log('\n🔍 Verifying collection...')
+  let gatedMinter: PublicKey | null = null
   try {
     const collectionStateAccount: any =
       await splNftProgram.account.collectionState.fetch(collectionState)
@@ -99,12 +100,19 @@ async function main() {
     console.log('  Current counter:', collectionStateAccount.counter.toString())
     console.log('  URI Prefix:', collectionStateAccount.uriPrefix)
     console.log('  Price:', collectionStateAccount.price.toString(), 'KRW')
+    gatedMinter = collectionStateAccount.gatedMinter
   } catch (error) {
     console.error('\n❌ Collection not found or invalid!')
     console.error('Please check the collection mint address.')
     process.exit(1)
   }
 
+  if (gatedMinter) {
+    console.error('\n❌ Collection only mints through', gatedMinter.toBase58())
+    console.error('Mint via zk-escrow, or clear the gated minter first.')
+    process.exit(1)
+  }
+
   // Generate NFT mint
   const nftKeypair = Keypair.generate()
   const nftMint = nftKeypair.publicKey
@@ -125,13 +133,14 @@ async function main() {
   // Mint NFT
   // Note: destination, mintAuthority, collectionState are auto-resolved PDAs
   const tx = await splNftProgram.methods
-    .mintNft()
+    .mintNft(null, null, null)
     .accounts({
       owner: owner.publicKey,
       mint: nftMint,
       metadata: nftMetadata,
       masterEdition: nftMasterEdition,
       collectionMint: collectionMintAddress,
+      nftAttributes: null,
     })
     .signers([nftKeypair])
     .rpc()
@@ -145,13 +145,21 @@ describe('Integration Test - ZK Proof Verification and NFT Mint', () => {
 
     // Create collection NFT
     const createCollectionTx = await splNftProgram.methods
-      .createCollection(
-        'KCONA_MOVIE1', // name
-        'KMOVIE1', // symbol
-        'https://kcona.io/movie/_collection.json', // collection uri
-        'https://kcona.io/movie/json', // uri prefix
-        new anchor.BN(1000), // price (1000 KRW)
-      )
+      .createCollection({
+        name: 'KCONA_MOVIE1',
+        symbol: 'KMOVIE1',
+        collectionUri: 'https://kcona.io/movie/_collection.json',
+        uriPrefix: 'https://kcona.io/movie/json',
+        price: new anchor.BN(1000), // 1000 KRW
+        maxMintsPerWallet: 0, // unlimited
+        maxSupply: new anchor.BN(0), // unlimited
+        soulbound: true,
+        programmable: false,
+        ruleSet: null,
+        sellerFeeBasisPoints: 0,
+        creators: [],
+        gatedMinter: zkEscrowSolProgram.programId,
+      })
       .accounts({
         user: payer.publicKey,
         mint: collectionMint,
@@ -173,6 +181,36 @@ describe('Integration Test - ZK Proof Verification and NFT Mint', () => {
     console.log('  - URI Prefix:', collectionStateAccount.uriPrefix)
     console.log('  - Price:', collectionStateAccount.price.toString(), 'KRW')
     console.log('  - Counter:', collectionStateAccount.counter.toString())
+    expect(collectionStateAccount.gatedMinter.toBase58()).to.equal(
+      zkEscrowSolProgram.programId.toBase58(),
+    )
+  })
+
+  it('Step 0b: Direct spl_nft mint is rejected for gated collections', async () => {
+    const directMintKeypair = Keypair.generate()
+    const directMint = directMintKeypair.publicKey
+
+    try {
+      await splNftProgram.methods
+        .mintNft(null, null, null)
+        .accounts({
+          owner: payer.publicKey,
+          mint: directMint,
+          metadata: await getMetadata(directMint),
+          masterEdition: await getMasterEdition(directMint),
+          collectionMint,
+          tokenRecord: null,
+          sysvarInstruction: SYSVAR_INSTRUCTIONS_PUBKEY,
+          authorizationRulesProgram: null,
+          authorizationRules: null,
+          nftAttributes: null,
+        })
+        .signers([directMintKeypair])
+        .rpc()
+      expect.fail('Direct mint should be rejected')
+    } catch (err) {
+      expect(err.toString()).to.include('UnauthorizedMinter')
+    }
   })
 
   it('Step 1: Initialize payment config in verification program', async () => {
@@ -211,6 +249,26 @@ describe('Integration Test - ZK Proof Verification and NFT Mint', () => {
     console.log('  - Currency:', paymentConfig.fiatCurrency)
   })
 
+  it('Step 1b: Initialize the witness registry proofs are counted against', async () => {
+    const fixture = loadProof()
+
+    try {
+      await zkEscrowSolProgram.methods
+        .initializeWitnessRegistry([fixture.expectedWitness], 1)
+        .accounts({
+          authority: payer.publicKey,
+        })
+        .rpc()
+      console.log('✅ Witness registry initialized')
+    } catch (e: any) {
+      if (e.message && e.message.includes('already in use')) {
+        console.log('✅ Witness registry already initialized')
+      } else {
+        throw e
+      }
+    }
+  })
+
   it('Step 2a: User verifies proof (Transaction 1/2)', async () => {
     console.log('\n=== Test: Verify Proof (Transaction 1) ===')
 
@@ -230,6 +288,7 @@ describe('Integration Test - ZK Proof Verification and NFT Mint', () => {
         claim: {
           identifier: fixture.signedClaim.claim.identifier, // Use original identifier (signatures are valid for this)
           owner: fixture.signedClaim.claim.owner,
+          ownerFormat: { ethereum: {} },
           timestampS: fixture.signedClaim.claim.timestampS,
           epoch: fixture.signedClaim.claim.epoch,
         },
@@ -325,10 +384,11 @@ describe('Integration Test - ZK Proof Verification and NFT Mint', () => {
     })
 
     // Transaction 2: Sponsor pays for minting, but NFT goes to user
+    // The user co-signs so the soulbound NFT is delegated and can be burned later
     console.log('\n💰 Sponsor signs and pays for NFT minting')
     console.log('   → NFT will be sent to user:', user.publicKey.toBase58())
     const tx = await zkEscrowSolProgram.methods
-      .mintWithVerifiedProof()
+      .mintWithVerifiedProof(null, null)
       .accounts({
         signer: sponsor.publicKey,  // Sponsor pays
         verificationResult: verificationResultPda,  // Contains user pubkey
@@ -344,7 +404,7 @@ describe('Integration Test - ZK Proof Verification and NFT Mint', () => {
         tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
       })
       .preInstructions([computeBudgetIx])
-      .signers([mintKeypair])
+      .signers([mintKeypair, user]) // Soulbound: the user co-signs
       .rpc({
         skipPreflight: true,
       })
@@ -365,6 +425,20 @@ describe('Integration Test - ZK Proof Verification and NFT Mint', () => {
     console.log('✅ Verification result PDA remains open (reusable)')
     console.log('  - Can be used for future mints with new proofs')
 
+    // Claim attributes are stored next to the NFT
+    const [nftAttributesPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from('attributes'), mint.toBuffer()],
+      splNftProgram.programId,
+    )
+    const nftAttributes =
+      await splNftProgram.account.nftAttributes.fetch(nftAttributesPda)
+    expect(nftAttributes.mint.toBase58()).to.equal(mint.toBase58())
+    expect(nftAttributes.attributes.claimIdentifier).to.equal(
+      verificationResult.claimIdentifier,
+    )
+    expect(nftAttributes.attributes.amountBucket).to.equal(4) // 1000 KRW
+    console.log('✅ Claim attributes stored for the NFT')
+
     // Wait a bit for account to be fully created
     await new Promise((resolve) => setTimeout(resolve, 2000))
   })
@@ -471,6 +545,7 @@ describe('Integration Test - ZK Proof Verification and NFT Mint', () => {
     expect(destinationAccount.mint.toBase58()).to.equal(mint.toBase58())
     expect(destinationAccount.owner.toBase58()).to.equal(user.publicKey.toBase58())
     expect(destinationAccount.amount.toString()).to.equal('1')
+    expect(destinationAccount.isFrozen).to.be.true
     console.log('✅ Verified: destination owns the first NFT (balance = 1)')
 
     // Verify NFT data
@@ -510,7 +585,7 @@ describe('Integration Test - ZK Proof Verification and NFT Mint', () => {
     // Mint second NFT with verified proof (sponsor pays again)
     console.log('\n💰 Sponsor mints 2nd NFT for user')
     const tx = await zkEscrowSolProgram.methods
-      .mintWithVerifiedProof()
+      .mintWithVerifiedProof(null, null)
       .accounts({
         signer: sponsor.publicKey,  // Sponsor pays
         verificationResult: verificationResultPda,  // Same verification PDA
@@ -526,7 +601,7 @@ describe('Integration Test - ZK Proof Verification and NFT Mint', () => {
         tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
       })
       .preInstructions([computeBudgetIx2])
-      .signers([mint2Keypair])
+      .signers([mint2Keypair, user])
       .rpc({
         skipPreflight: true,
       })
@@ -567,4 +642,125 @@ describe('Integration Test - ZK Proof Verification and NFT Mint', () => {
     expect(destination2Account.amount.toString()).to.equal('1')
     console.log('✅ Verified: destination2 owns the second NFT (balance = 1)')
   })
+
+  it('Step 5: User burns the soulbound NFT minted through zk-escrow', async () => {
+    console.log('\n=== Test: Burn Soulbound NFT ===')
+
+    const destinationAccount = await getAccount(connection, destination)
+    expect(destinationAccount.isFrozen).to.be.true
+    expect(destinationAccount.delegate?.toBase58()).to.equal(
+      mintAuthority.toBase58(),
+    )
+
+    const [mintRecord] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from('mint_record'), mint.toBuffer()],
+      splNftProgram.programId,
+    )
+    const [nftAttributes] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from('attributes'), mint.toBuffer()],
+      splNftProgram.programId,
+    )
+
+    const tx = await splNftProgram.methods
+      .burnNft()
+      .accountsStrict({
+        authority: user.publicKey,
+        mintAuthority,
+        collectionMint,
+        collectionState,
+        mint,
+        token: destination,
+        holder: user.publicKey,
+        metadata,
+        masterEdition,
+        collectionMetadata,
+        tokenRecord: null,
+        mintRecord,
+        nftAttributes,
+        systemProgram: SystemProgram.programId,
+        sysvarInstruction: SYSVAR_INSTRUCTIONS_PUBKEY,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+      })
+      .preInstructions([
+        ComputeBudgetProgram.setComputeUnitLimit({ units: 400_000 }),
+      ])
+      .signers([user])
+      .rpc()
+    console.log('✅ NFT burned, tx:', tx)
+
+    expect(await connection.getAccountInfo(metadata)).to.be.null
+    expect(await connection.getAccountInfo(mintRecord)).to.be.null
+    expect(await connection.getAccountInfo(nftAttributes)).to.be.null
+  })
+
+  it('Step 6: Minting through zk-escrow into an ungated collection skips attributes', async () => {
+    console.log('\n=== Test: Mint into Ungated Collection ===')
+
+    const ungatedKeypair = Keypair.generate()
+    const ungatedMint = ungatedKeypair.publicKey
+    const ungatedMetadata = await getMetadata(ungatedMint)
+    const ungatedMasterEdition = await getMasterEdition(ungatedMint)
+
+    await splNftProgram.methods
+      .createCollection({
+        name: 'KCONA_OPEN',
+        symbol: 'KOPEN',
+        collectionUri: 'https://kcona.io/open/_collection.json',
+        uriPrefix: 'https://kcona.io/open/json',
+        price: new anchor.BN(1000),
+        maxMintsPerWallet: 0,
+        maxSupply: new anchor.BN(0),
+        soulbound: false,
+        programmable: false,
+        ruleSet: null,
+        sellerFeeBasisPoints: 0,
+        creators: [],
+        gatedMinter: null,
+      })
+      .accounts({
+        user: payer.publicKey,
+        mint: ungatedMint,
+        metadata: ungatedMetadata,
+        masterEdition: ungatedMasterEdition,
+      })
+      .signers([ungatedKeypair])
+      .rpc()
+
+    const nftKeypair = Keypair.generate()
+    const nftMint = nftKeypair.publicKey
+    const nftDestination = getAssociatedTokenAddressSync(nftMint, user.publicKey)
+
+    await zkEscrowSolProgram.methods
+      .mintWithVerifiedProof(null, null)
+      .accounts({
+        signer: sponsor.publicKey,
+        verificationResult: verificationResultPda,
+        nftRecipient: user.publicKey,
+        mint: nftMint,
+        destination: nftDestination,
+        metadata: await getMetadata(nftMint),
+        masterEdition: await getMasterEdition(nftMint),
+        mintAuthority: mintAuthority,
+        collectionMint: ungatedMint,
+        collectionMetadata: ungatedMetadata,
+        collectionMasterEdition: ungatedMasterEdition,
+        tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+      })
+      .preInstructions([
+        ComputeBudgetProgram.setComputeUnitLimit({ units: 400_000 }),
+      ])
+      .signers([nftKeypair])
+      .rpc()
+
+    const nftDestinationAccount = await getAccount(connection, nftDestination)
+    expect(nftDestinationAccount.amount.toString()).to.equal('1')
+
+    const [nftAttributes] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from('attributes'), nftMint.toBuffer()],
+      splNftProgram.programId,
+    )
+    expect(await connection.getAccountInfo(nftAttributes)).to.be.null
+    console.log('✅ Minted without claim attributes')
+  })
 })
@@ -5,6 +5,7 @@ import { ASSOCIATED_PROGRAM_ID } from '@coral-xyz/anchor/dist/cjs/utils/token'
 import {
   ASSOCIATED_TOKEN_PROGRAM_ID,
   TOKEN_PROGRAM_ID,
+  getAccount,
   getAssociatedTokenAddressSync,
 } from '@solana/spl-token'
 import { Keypair, SystemProgram } from '@solana/web3.js'
@@ -40,6 +41,37 @@ describe('mint-nft', () => {
   const mintKeypair = Keypair.generate()
   const mint = mintKeypair.publicKey
 
+  const getWalletMintCounter = (
+    owner: anchor.web3.PublicKey,
+  ): anchor.web3.PublicKey => {
+    return anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from('wallet_mints'),
+        collectionMint.toBuffer(),
+        owner.toBuffer(),
+      ],
+      program.programId,
+    )[0]
+  }
+
+  const getMintRecord = (
+    mint: anchor.web3.PublicKey,
+  ): anchor.web3.PublicKey => {
+    return anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from('mint_record'), mint.toBuffer()],
+      program.programId,
+    )[0]
+  }
+
+  const getNftAttributes = (
+    mint: anchor.web3.PublicKey,
+  ): anchor.web3.PublicKey => {
+    return anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from('attributes'), mint.toBuffer()],
+      program.programId,
+    )[0]
+  }
+
   const getMetadata = async (
     mint: anchor.web3.PublicKey,
   ): Promise<anchor.web3.PublicKey> => {
@@ -83,13 +115,21 @@ describe('mint-nft', () => {
     console.log('Destination ATA = ', destination.toBase58())
 
     const tx = await program.methods
-      .createCollection(
-        'KCONA', // name
-        'KCONA', // symbol
-        'https://kcona.io/metadata/_collection.json', // collection uri
-        'https://kcona.io/metadata/json', // uri prefix
-        new anchor.BN(1000), // price (1000 KRW)
-      )
+      .createCollection({
+        name: 'KCONA',
+        symbol: 'KCONA',
+        collectionUri: 'https://kcona.io/metadata/_collection.json',
+        uriPrefix: 'https://kcona.io/metadata/json',
+        price: new anchor.BN(1000), // 1000 KRW
+        maxMintsPerWallet: 1,
+        maxSupply: new anchor.BN(10),
+        soulbound: true,
+        programmable: false,
+        ruleSet: null,
+        sellerFeeBasisPoints: 500, // 5%
+        creators: [{ address: wallet.publicKey, share: 100 }],
+        gatedMinter: null,
+      })
       .accountsStrict({
         user: wallet.publicKey,
         mint: collectionMint,
@@ -123,7 +163,7 @@ describe('mint-nft', () => {
     console.log('Destination', destination.toBase58())
 
     const tx = await program.methods
-      .mintNft()
+      .mintNft(null, null, null)
       .accountsStrict({
         payer: wallet.publicKey,
         owner: wallet.publicKey,
@@ -134,10 +174,17 @@ describe('mint-nft', () => {
         mintAuthority,
         collectionMint,
         collectionState,
+        walletMintCounter: getWalletMintCounter(wallet.publicKey),
+        mintRecord: getMintRecord(mint),
         systemProgram: SystemProgram.programId,
         tokenProgram: TOKEN_PROGRAM_ID,
         associatedTokenProgram: ASSOCIATED_PROGRAM_ID,
         tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+        tokenRecord: null,
+        sysvarInstruction: null,
+        authorizationRulesProgram: null,
+        authorizationRules: null,
+        nftAttributes: null,
       })
       .signers([mintKeypair])
       .rpc({
@@ -183,6 +230,21 @@ describe('mint-nft', () => {
       1000,
       'Price should be 1000 KRW',
     )
+    assert.strictEqual(
+      collectionStateAccount.maxSupply.toNumber(),
+      10,
+      'Max supply should be 10',
+    )
+    assert.strictEqual(
+      collectionStateAccount.sellerFeeBasisPoints,
+      500,
+      'Seller fee should be 500 bps',
+    )
+    assert.strictEqual(
+      collectionStateAccount.creators[0].address.toBase58(),
+      wallet.publicKey.toBase58(),
+      'Configured creator should be stored',
+    )
 
     // Fetch metadata account to verify URI
     const metadataAccountInfo = await provider.connection.getAccountInfo(
@@ -201,10 +263,78 @@ describe('mint-nft', () => {
       `Metadata should contain URI: ${uriExpected}`,
     )
 
+    const destinationAccount = await getAccount(provider.connection, destination)
+    assert.ok(destinationAccount.isFrozen, 'Soulbound NFT should be frozen')
+
+    assert.ok(
+      metadataString.includes('KCONA #1'),
+      'Metadata name should include the edition number',
+    )
+    const mintRecord = await program.account.mintRecord.fetch(
+      getMintRecord(mint),
+    )
+    assert.strictEqual(
+      mintRecord.editionNumber.toNumber(),
+      1,
+      'Edition number should be 1',
+    )
+
+    const walletMintCounter = await program.account.walletMintCounter.fetch(
+      getWalletMintCounter(wallet.publicKey),
+    )
+    assert.strictEqual(
+      walletMintCounter.count,
+      1,
+      'Wallet mint counter should be 1',
+    )
+
     console.log('✓ All assertions passed!')
     console.log(`✓ NFT minted with URI: ${uriExpected}`)
   })
 
+  it('Rejects mints beyond the per-wallet limit', async () => {
+    const extraMintKeypair = Keypair.generate()
+    const extraMint = extraMintKeypair.publicKey
+
+    try {
+      await program.methods
+        .mintNft(null, null, null)
+        .accountsStrict({
+          payer: wallet.publicKey,
+          owner: wallet.publicKey,
+          destination: getAssociatedTokenAddressSync(
+            extraMint,
+            wallet.publicKey,
+          ),
+          metadata: await getMetadata(extraMint),
+          masterEdition: await getMasterEdition(extraMint),
+          mint: extraMint,
+          mintAuthority,
+          collectionMint,
+          collectionState,
+          walletMintCounter: getWalletMintCounter(wallet.publicKey),
+          mintRecord: getMintRecord(extraMint),
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_PROGRAM_ID,
+          tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+          tokenRecord: null,
+          sysvarInstruction: null,
+          authorizationRulesProgram: null,
+          authorizationRules: null,
+          nftAttributes: null,
+        })
+        .signers([extraMintKeypair])
+        .rpc()
+      assert.fail('Second mint to the same wallet should fail')
+    } catch (err) {
+      assert.ok(
+        err.toString().includes('WalletMintLimitReached'),
+        `Unexpected error: ${err}`,
+      )
+    }
+  })
+
   it('Verify Collection', async () => {
     const mintMetadata = await getMetadata(mint)
     console.log('\nMint Metadata', mintMetadata.toBase58())
@@ -234,4 +364,535 @@ describe('mint-nft', () => {
       })
     console.log('\nCollection Verified! Your transaction signature', tx)
   })
+
+  it('Update Collection', async () => {
+    await program.methods
+      .updateCollection('https://kcona.io/metadata/v2', new anchor.BN(2000))
+      .accountsStrict({
+        authority: wallet.publicKey,
+        collectionMint,
+        collectionState,
+      })
+      .rpc()
+
+    const collectionStateAccount = await program.account.collectionState.fetch(
+      collectionState,
+    )
+    assert.strictEqual(
+      collectionStateAccount.uriPrefix,
+      'https://kcona.io/metadata/v2',
+      'URI prefix should be updated',
+    )
+    assert.strictEqual(
+      collectionStateAccount.price.toNumber(),
+      2000,
+      'Price should be updated',
+    )
+    assert.strictEqual(
+      collectionStateAccount.authority.toBase58(),
+      wallet.publicKey.toBase58(),
+      'Collection authority should be the creator',
+    )
+  })
+
+  it('Pause and resume minting', async () => {
+    await program.methods
+      .setMintPaused(true)
+      .accountsStrict({
+        authority: wallet.publicKey,
+        collectionMint,
+        collectionState,
+      })
+      .rpc()
+
+    const pausedMintKeypair = Keypair.generate()
+    const pausedMint = pausedMintKeypair.publicKey
+    try {
+      await program.methods
+        .mintNft(null, null, null)
+        .accountsStrict({
+          payer: wallet.publicKey,
+          owner: wallet.publicKey,
+          destination: getAssociatedTokenAddressSync(
+            pausedMint,
+            wallet.publicKey,
+          ),
+          metadata: await getMetadata(pausedMint),
+          masterEdition: await getMasterEdition(pausedMint),
+          mint: pausedMint,
+          mintAuthority,
+          collectionMint,
+          collectionState,
+          walletMintCounter: getWalletMintCounter(wallet.publicKey),
+          mintRecord: getMintRecord(pausedMint),
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_PROGRAM_ID,
+          tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+          tokenRecord: null,
+          sysvarInstruction: null,
+          authorizationRulesProgram: null,
+          authorizationRules: null,
+          nftAttributes: null,
+        })
+        .signers([pausedMintKeypair])
+        .rpc()
+      assert.fail('Mint should fail while paused')
+    } catch (err) {
+      assert.ok(
+        err.toString().includes('MintPaused'),
+        `Unexpected error: ${err}`,
+      )
+    }
+
+    await program.methods
+      .setMintPaused(false)
+      .accountsStrict({
+        authority: wallet.publicKey,
+        collectionMint,
+        collectionState,
+      })
+      .rpc()
+
+    const collectionStateAccount = await program.account.collectionState.fetch(
+      collectionState,
+    )
+    assert.strictEqual(
+      collectionStateAccount.mintPaused,
+      false,
+      'Minting should be resumed',
+    )
+  })
+
+  const mintToHolder = async (
+    holder: Keypair,
+    holderSigns: boolean,
+  ): Promise<anchor.web3.PublicKey> => {
+    const holderMintKeypair = Keypair.generate()
+    const holderMint = holderMintKeypair.publicKey
+    await program.methods
+      .mintNft(null, null, null)
+      .accountsStrict({
+        payer: wallet.publicKey,
+        owner: holder.publicKey,
+        destination: getAssociatedTokenAddressSync(
+          holderMint,
+          holder.publicKey,
+        ),
+        metadata: await getMetadata(holderMint),
+        masterEdition: await getMasterEdition(holderMint),
+        mint: holderMint,
+        mintAuthority,
+        collectionMint,
+        collectionState,
+        walletMintCounter: getWalletMintCounter(holder.publicKey),
+        mintRecord: getMintRecord(holderMint),
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_PROGRAM_ID,
+        tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+        tokenRecord: null,
+        sysvarInstruction: null,
+        authorizationRulesProgram: null,
+        authorizationRules: null,
+        nftAttributes: null,
+      })
+      .signers(
+        holderSigns ? [holderMintKeypair, holder] : [holderMintKeypair],
+      )
+      .rpc()
+    return holderMint
+  }
+
+  const burnAsAuthority = async (
+    holder: Keypair,
+    holderMint: anchor.web3.PublicKey,
+  ) => {
+    await program.methods
+      .burnNft()
+      .accountsStrict({
+        authority: wallet.publicKey,
+        mintAuthority,
+        collectionMint,
+        collectionState,
+        mint: holderMint,
+        token: getAssociatedTokenAddressSync(holderMint, holder.publicKey),
+        holder: holder.publicKey,
+        metadata: await getMetadata(holderMint),
+        masterEdition: await getMasterEdition(holderMint),
+        collectionMetadata: await getMetadata(collectionMint),
+        tokenRecord: null,
+        mintRecord: getMintRecord(holderMint),
+        nftAttributes: getNftAttributes(holderMint),
+        systemProgram: SystemProgram.programId,
+        sysvarInstruction: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+      })
+      .rpc()
+  }
+
+  it('Collection authority burns a delegated soulbound NFT', async () => {
+    const holder = Keypair.generate()
+    const holderMint = await mintToHolder(holder, true)
+
+    const holderToken = await getAccount(
+      provider.connection,
+      getAssociatedTokenAddressSync(holderMint, holder.publicKey),
+    )
+    assert.ok(holderToken.isFrozen, 'Soulbound NFT should be frozen')
+    assert.strictEqual(
+      holderToken.delegate?.toBase58(),
+      mintAuthority.toBase58(),
+      'Authority PDA should be the delegate of a holder-signed mint',
+    )
+
+    await burnAsAuthority(holder, holderMint)
+
+    assert.strictEqual(
+      await provider.connection.getAccountInfo(await getMetadata(holderMint)),
+      null,
+      'Metadata should be closed',
+    )
+  })
+
+  it('Rejects soulbound mints the owner does not sign', async () => {
+    const holder = Keypair.generate()
+
+    try {
+      await mintToHolder(holder, false)
+      assert.fail('Soulbound mint without the owner signature should fail')
+    } catch (err) {
+      assert.ok(
+        err.toString().includes('SoulboundMintUnsigned'),
+        `Unexpected error: ${err}`,
+      )
+    }
+  })
+
+  it('Gate and ungate minting', async () => {
+    const gatedMinter = Keypair.generate().publicKey
+    await program.methods
+      .setGatedMinter(gatedMinter)
+      .accountsStrict({
+        authority: wallet.publicKey,
+        collectionMint,
+        collectionState,
+      })
+      .rpc()
+
+    const holder = Keypair.generate()
+    try {
+      await mintToHolder(holder, false)
+      assert.fail('Direct mint should fail while gated')
+    } catch (err) {
+      assert.ok(
+        err.toString().includes('UnauthorizedMinter'),
+        `Unexpected error: ${err}`,
+      )
+    }
+
+    try {
+      await program.methods
+        .setGatedMinter(null)
+        .accountsStrict({
+          authority: holder.publicKey,
+          collectionMint,
+          collectionState,
+        })
+        .signers([holder])
+        .rpc()
+      assert.fail('Only the collection authority can change the gated minter')
+    } catch (err) {
+      assert.ok(
+        err.toString().includes('ConstraintHasOne'),
+        `Unexpected error: ${err}`,
+      )
+    }
+
+    await program.methods
+      .setGatedMinter(null)
+      .accountsStrict({
+        authority: wallet.publicKey,
+        collectionMint,
+        collectionState,
+      })
+      .rpc()
+
+    const collectionStateAccount = await program.account.collectionState.fetch(
+      collectionState,
+    )
+    assert.strictEqual(
+      collectionStateAccount.gatedMinter,
+      null,
+      'Minting should be open again',
+    )
+  })
+
+  it('Rejects claim attributes outside a gated minter', async () => {
+    const holder = Keypair.generate()
+    const attributesMintKeypair = Keypair.generate()
+    const attributesMint = attributesMintKeypair.publicKey
+
+    try {
+      await program.methods
+        .mintNft(null, null, {
+          claimIdentifier: '0x' + '00'.repeat(32),
+          amountBucket: 4,
+          verifiedAt: new anchor.BN(0),
+        })
+        .accountsStrict({
+          payer: wallet.publicKey,
+          owner: holder.publicKey,
+          destination: getAssociatedTokenAddressSync(
+            attributesMint,
+            holder.publicKey,
+          ),
+          metadata: await getMetadata(attributesMint),
+          masterEdition: await getMasterEdition(attributesMint),
+          mint: attributesMint,
+          mintAuthority,
+          collectionMint,
+          collectionState,
+          walletMintCounter: getWalletMintCounter(holder.publicKey),
+          mintRecord: getMintRecord(attributesMint),
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_PROGRAM_ID,
+          tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+          tokenRecord: null,
+          sysvarInstruction: null,
+          authorizationRulesProgram: null,
+          authorizationRules: null,
+          nftAttributes: getNftAttributes(attributesMint),
+        })
+        .signers([attributesMintKeypair])
+        .rpc()
+      assert.fail('Attributes on an ungated collection should be rejected')
+    } catch (err) {
+      assert.ok(
+        err.toString().includes('AttributesRequireGatedMinter'),
+        `Unexpected error: ${err}`,
+      )
+    }
+  })
+
+  const pnftCollectionKeypair = Keypair.generate()
+  const pnftCollectionMint = pnftCollectionKeypair.publicKey
+  const [pnftCollectionState] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from('collection_state'), pnftCollectionMint.toBuffer()],
+    program.programId,
+  )
+  const pnftKeypair = Keypair.generate()
+  const pnftMint = pnftKeypair.publicKey
+  const pnftDestination = getAssociatedTokenAddressSync(
+    pnftMint,
+    wallet.publicKey,
+  )
+  const [pnftTokenRecord] = anchor.web3.PublicKey.findProgramAddressSync(
+    [
+      Buffer.from('metadata'),
+      TOKEN_METADATA_PROGRAM_ID.toBuffer(),
+      pnftMint.toBuffer(),
+      Buffer.from('token_record'),
+      pnftDestination.toBuffer(),
+    ],
+    TOKEN_METADATA_PROGRAM_ID,
+  )
+
+  it('Mint programmable NFT', async () => {
+
+    await program.methods
+      .createCollection({
+        name: 'KCONA PNFT',
+        symbol: 'KPNFT',
+        collectionUri: 'https://kcona.io/pnft/_collection.json',
+        uriPrefix: 'https://kcona.io/pnft/json',
+        price: new anchor.BN(1000),
+        maxMintsPerWallet: 0, // unlimited
+        maxSupply: new anchor.BN(0), // unlimited
+        soulbound: false,
+        programmable: true,
+        ruleSet: null,
+        sellerFeeBasisPoints: 0,
+        creators: [],
+        gatedMinter: null,
+      })
+      .accountsStrict({
+        user: wallet.publicKey,
+        mint: pnftCollectionMint,
+        collectionState: pnftCollectionState,
+        mintAuthority,
+        metadata: await getMetadata(pnftCollectionMint),
+        masterEdition: await getMasterEdition(pnftCollectionMint),
+        destination: getAssociatedTokenAddressSync(
+          pnftCollectionMint,
+          wallet.publicKey,
+        ),
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+      })
+      .signers([pnftCollectionKeypair])
+      .rpc()
+
+    const [walletMintCounter] = anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from('wallet_mints'),
+        pnftCollectionMint.toBuffer(),
+        wallet.publicKey.toBuffer(),
+      ],
+      program.programId,
+    )
+
+    await program.methods
+      .mintNft(
+        'KCONA PNFT Special', // name override
+        'https://kcona.io/pnft/json/special.json', // uri override
+        null, // claim attributes
+      )
+      .accountsStrict({
+        payer: wallet.publicKey,
+        owner: wallet.publicKey,
+        destination: pnftDestination,
+        metadata: await getMetadata(pnftMint),
+        masterEdition: await getMasterEdition(pnftMint),
+        mint: pnftMint,
+        mintAuthority,
+        collectionMint: pnftCollectionMint,
+        collectionState: pnftCollectionState,
+        walletMintCounter,
+        mintRecord: getMintRecord(pnftMint),
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_PROGRAM_ID,
+        tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+        tokenRecord: pnftTokenRecord,
+        sysvarInstruction: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+        authorizationRulesProgram: null,
+        authorizationRules: null,
+        nftAttributes: null,
+      })
+      .preInstructions([
+        anchor.web3.ComputeBudgetProgram.setComputeUnitLimit({
+          units: 400_000,
+        }),
+      ])
+      .signers([pnftKeypair])
+      .rpc()
+
+    const tokenRecordInfo = await provider.connection.getAccountInfo(
+      pnftTokenRecord,
+    )
+    assert.ok(tokenRecordInfo, 'Token record should be created for the pNFT')
+
+    const metadataInfo = await provider.connection.getAccountInfo(
+      await getMetadata(pnftMint),
+    )
+    assert.ok(
+      metadataInfo.data
+        .toString()
+        .includes('https://kcona.io/pnft/json/special.json'),
+      'Metadata should use the URI override',
+    )
+
+    const destinationAccount = await getAccount(
+      provider.connection,
+      pnftDestination,
+    )
+    assert.strictEqual(destinationAccount.amount.toString(), '1')
+    assert.ok(destinationAccount.isFrozen, 'pNFT token account should be frozen')
+  })
+
+  it('Sunset and close collection', async () => {
+    await program.methods
+      .sunsetCollection()
+      .accountsStrict({
+        authority: wallet.publicKey,
+        collectionMint: pnftCollectionMint,
+        collectionState: pnftCollectionState,
+      })
+      .rpc()
+
+    const [walletMintCounter] = anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from('wallet_mints'),
+        pnftCollectionMint.toBuffer(),
+        wallet.publicKey.toBuffer(),
+      ],
+      program.programId,
+    )
+
+    await program.methods
+      .closeCollection()
+      .accountsStrict({
+        authority: wallet.publicKey,
+        collectionMint: pnftCollectionMint,
+        collectionState: pnftCollectionState,
+      })
+      .remainingAccounts([
+        { pubkey: walletMintCounter, isWritable: true, isSigner: false },
+      ])
+      .rpc()
+
+    assert.strictEqual(
+      await provider.connection.getAccountInfo(pnftCollectionState),
+      null,
+      'Collection state should be closed',
+    )
+    assert.strictEqual(
+      await provider.connection.getAccountInfo(walletMintCounter),
+      null,
+      'Wallet mint counter should be closed',
+    )
+  })
+
+  it('Burn NFT after the collection is closed', async () => {
+    const pnftMetadata = await getMetadata(pnftMint)
+
+    await program.methods
+      .burnNft()
+      .accountsStrict({
+        authority: wallet.publicKey,
+        mintAuthority,
+        collectionMint: pnftCollectionMint,
+        collectionState: null,
+        mint: pnftMint,
+        token: pnftDestination,
+        holder: wallet.publicKey,
+        metadata: pnftMetadata,
+        masterEdition: await getMasterEdition(pnftMint),
+        collectionMetadata: await getMetadata(pnftCollectionMint),
+        tokenRecord: pnftTokenRecord,
+        mintRecord: getMintRecord(pnftMint),
+        nftAttributes: getNftAttributes(pnftMint),
+        systemProgram: SystemProgram.programId,
+        sysvarInstruction: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+      })
+      .preInstructions([
+        anchor.web3.ComputeBudgetProgram.setComputeUnitLimit({
+          units: 400_000,
+        }),
+      ])
+      .rpc()
+
+    assert.strictEqual(
+      await provider.connection.getAccountInfo(pnftDestination),
+      null,
+      'Token account should be closed',
+    )
+    assert.strictEqual(
+      await provider.connection.getAccountInfo(pnftMetadata),
+      null,
+      'Metadata should be closed',
+    )
+    assert.strictEqual(
+      await provider.connection.getAccountInfo(getMintRecord(pnftMint)),
+      null,
+      'Mint record should be closed',
+    )
+  })
 })
@@ -3,6 +3,8 @@ import type { Program } from '@coral-xyz/anchor'
 import { SystemProgram, Keypair } from '@solana/web3.js'
 import type { NullifierRegistry } from '../target/types/nullifier_registry'
 import assert from 'assert'
+import { keccak256 } from 'ethers'
+import { calculateNullifier } from './utils'
 
 describe('nullifier-registry', () => {
   const provider = anchor.AnchorProvider.env()
@@ -13,37 +15,115 @@ describe('nullifier-registry', () => {
   const nullifierProgram = anchor.workspace
     .nullifierRegistry as Program<NullifierRegistry>
 
+  // Each test run uses the wallet as its registry namespace
+  const namespace = wallet.publicKey
+
+  // First registry created by the wallet
+  const registryId = new anchor.BN(0)
+
+  const [authorityRegistries] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from('authority_registries'), wallet.publicKey.toBuffer()],
+    nullifierProgram.programId,
+  )
+
   const [nullifierRegistry] = anchor.web3.PublicKey.findProgramAddressSync(
-    [Buffer.from('nullifier_registry')],
+    [
+      Buffer.from('nullifier_registry'),
+      wallet.publicKey.toBuffer(),
+      registryId.toArrayLike(Buffer, 'le', 8),
+    ],
+    nullifierProgram.programId,
+  )
+
+  const [stats] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from('stats'), nullifierRegistry.toBuffer()],
+    nullifierProgram.programId,
+  )
+
+  const [rateLimit] = anchor.web3.PublicKey.findProgramAddressSync(
+    [
+      Buffer.from('rate_limit'),
+      nullifierRegistry.toBuffer(),
+      wallet.publicKey.toBuffer(),
+    ],
     nullifierProgram.programId,
   )
 
   // Test nullifier hash: [1, 1, 1, 1, ..., 1] (32 bytes)
   const testNullifierHash = Array.from(Buffer.alloc(32, 1))
 
+  // Claim identifier hash recorded alongside each nullifier
+  const testClaimIdentifierHash = Array.from(Buffer.alloc(32, 9))
+
   const [nullifierRecord] = anchor.web3.PublicKey.findProgramAddressSync(
-    [Buffer.from('nullifier'), Buffer.from(testNullifierHash)],
+    [
+      Buffer.from('nullifier'),
+      nullifierRegistry.toBuffer(),
+      Buffer.from(testNullifierHash),
+    ],
     nullifierProgram.programId,
   )
 
+  // Creates the wallet's next registry, for tests that need one without marks
+  const createFreshRegistry = async () => {
+    const { registryCount } =
+      await nullifierProgram.account.authorityRegistries.fetch(
+        authorityRegistries,
+      )
+    const [registry] = anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from('nullifier_registry'),
+        wallet.publicKey.toBuffer(),
+        registryCount.toArrayLike(Buffer, 'le', 8),
+      ],
+      nullifierProgram.programId,
+    )
+    const [registryStats] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from('stats'), registry.toBuffer()],
+      nullifierProgram.programId,
+    )
+    const [registryRateLimit] = anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from('rate_limit'),
+        registry.toBuffer(),
+        wallet.publicKey.toBuffer(),
+      ],
+      nullifierProgram.programId,
+    )
+
+    await nullifierProgram.methods
+      .initialize(namespace, registryCount)
+      .accountsStrict({
+        authorityRegistries,
+        registry,
+        stats: registryStats,
+        authority: wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc()
+
+    return { registry, stats: registryStats, rateLimit: registryRateLimit }
+  }
+
   it('Initialize nullifier registry', async () => {
     console.log('\nNullifier Registry:', nullifierRegistry.toBase58())
     console.log('Authority (wallet):', wallet.publicKey.toBase58())
 
     try {
       const tx = await nullifierProgram.methods
-        .initialize()
+        .initialize(namespace, registryId)
         .accountsStrict({
+          authorityRegistries,
           registry: nullifierRegistry,
+          stats,
           authority: wallet.publicKey,
-          user: wallet.publicKey,
           systemProgram: SystemProgram.programId,
         })
         .rpc()
 
       console.log('Registry initialized! TxID:', tx)
     } catch (error: any) {
-      if (error.message && error.message.includes('already in use')) {
+      if (error.message && error.message.includes('InvalidRegistryId')) {
         console.log('✅ Nullifier registry already initialized (from previous test)')
       } else {
         throw error
@@ -62,16 +142,38 @@ describe('nullifier-registry', () => {
     )
   })
 
+  it('list_registries derives the authority registries', async () => {
+    const registries = await nullifierProgram.methods
+      .listRegistries(new anchor.BN(0), 16)
+      .accountsStrict({ authorityRegistries })
+      .view()
+
+    assert.ok(registries.length >= 1, 'Wallet should own at least one registry')
+    assert.strictEqual(registries[0].toBase58(), nullifierRegistry.toBase58())
+  })
+
   it('Authorized wallet can mark nullifier', async () => {
     console.log('\n=== Testing Authorized Wallet ===')
 
     try {
       const tx = await nullifierProgram.methods
-        .markNullifier(testNullifierHash)
+        .markNullifier(
+          { hash32: [testNullifierHash] },
+          testClaimIdentifierHash,
+          false,
+          null,
+        )
         .accountsStrict({
           registry: nullifierRegistry,
+          callerAllowlist: null,
           nullifierRecord,
+          stats,
+          rateLimit,
+          bloomFilter: null,
           user: wallet.publicKey,
+          payer: wallet.publicKey,
+          callerAuthority: null,
+          callerProgram: null,
           systemProgram: SystemProgram.programId,
         })
         .rpc({
@@ -79,7 +181,7 @@ describe('nullifier-registry', () => {
         })
       console.log('✅ Authorized wallet successfully marked nullifier! TxID:', tx)
     } catch (error: any) {
-      if (error.message && error.message.includes('already in use')) {
+      if (error.message && error.message.includes('NullifierAlreadyUsed')) {
         console.log('✅ Nullifier already marked (from previous test)')
       } else {
         throw error
@@ -103,6 +205,209 @@ describe('nullifier-registry', () => {
       wallet.publicKey.toBase58(),
       'Used by should match payer',
     )
+    assert.deepStrictEqual(
+      recordAccount.claimIdentifierHash,
+      testClaimIdentifierHash,
+      'Claim identifier hash should match',
+    )
+    assert.strictEqual(
+      recordAccount.markedByProgram.toBase58(),
+      nullifierProgram.programId.toBase58(),
+      'Direct calls should be attributed to the registry program',
+    )
+    assert.ok(
+      recordAccount.sequence.gtn(0),
+      'Marked records should carry a registry sequence number',
+    )
+  })
+
+  it('Re-marking fails with NullifierAlreadyUsed unless idempotent', async () => {
+    const accounts = {
+      registry: nullifierRegistry,
+      callerAllowlist: null,
+      nullifierRecord,
+      stats,
+      rateLimit,
+      bloomFilter: null,
+      user: wallet.publicKey,
+      payer: wallet.publicKey,
+      callerAuthority: null,
+      callerProgram: null,
+      systemProgram: SystemProgram.programId,
+    }
+
+    try {
+      await nullifierProgram.methods
+        .markNullifier(
+          { hash32: [testNullifierHash] },
+          testClaimIdentifierHash,
+          false,
+          null,
+        )
+        .accountsStrict(accounts)
+        .rpc()
+      assert.fail('Re-marking should fail')
+    } catch (error: any) {
+      assert.ok(error.message.includes('NullifierAlreadyUsed'), error.message)
+    }
+
+    await nullifierProgram.methods
+      .markNullifier(
+        { hash32: [testNullifierHash] },
+        testClaimIdentifierHash,
+        true,
+        null,
+      )
+      .accountsStrict(accounts)
+      .rpc()
+  })
+
+  it('export_batch accepts consumed records of this registry', async () => {
+    await nullifierProgram.methods
+      .exportBatch()
+      .accountsStrict({
+        registry: nullifierRegistry,
+        authority: wallet.publicKey,
+      })
+      .remainingAccounts([
+        { pubkey: nullifierRecord, isWritable: false, isSigner: false },
+      ])
+      .rpc()
+  })
+
+  it('check_nullifier reports used status via return data', async () => {
+    const isUsed = await nullifierProgram.methods
+      .checkNullifier({ hash32: [testNullifierHash] }, false)
+      .accountsStrict({
+        registry: nullifierRegistry,
+        nullifierRecord,
+      })
+      .view()
+
+    assert.strictEqual(isUsed, true, 'Marked nullifier should be reported as used')
+  })
+
+  it('get_nullifier returns the record status via return data', async () => {
+    const status = await nullifierProgram.methods
+      .getNullifier({ hash32: [testNullifierHash] })
+      .accountsStrict({
+        registry: nullifierRegistry,
+        nullifierRecord,
+      })
+      .view()
+
+    assert.strictEqual(status.isUsed, true)
+    assert.strictEqual(status.usedBy.toBase58(), wallet.publicKey.toBase58())
+    assert.strictEqual(status.namespace.toBase58(), namespace.toBase58())
+  })
+
+  it('16-byte nullifiers use their own PDA domain', async () => {
+    // The main registry is pinned to 32-byte nullifiers by the earlier marks
+    const { registry, stats: registryStats, rateLimit: registryRateLimit } =
+      await createFreshRegistry()
+    const truncatedHash = Array.from(Buffer.alloc(16, 7))
+    const [truncatedRecord] = anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from('nullifier16'),
+        registry.toBuffer(),
+        Buffer.from(truncatedHash),
+      ],
+      nullifierProgram.programId,
+    )
+
+    await nullifierProgram.methods
+      .markNullifier(
+        { hash16: [truncatedHash] },
+        testClaimIdentifierHash,
+        true,
+        null,
+      )
+      .accountsStrict({
+        registry,
+        callerAllowlist: null,
+        nullifierRecord: truncatedRecord,
+        stats: registryStats,
+        rateLimit: registryRateLimit,
+        bloomFilter: null,
+        user: wallet.publicKey,
+        payer: wallet.publicKey,
+        callerAuthority: null,
+        callerProgram: null,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc()
+
+    const recordAccount =
+      await nullifierProgram.account.nullifierRecord.fetch(truncatedRecord)
+    assert.strictEqual(recordAccount.hashWidth, 16)
+
+    const isUsed = await nullifierProgram.methods
+      .checkNullifier({ hash16: [truncatedHash] }, false)
+      .accountsStrict({
+        registry,
+        nullifierRecord: truncatedRecord,
+      })
+      .view()
+    assert.strictEqual(isUsed, true)
+  })
+
+  it('A registry only accepts the nullifier width it was pinned to', async () => {
+    const truncatedHash = Array.from(Buffer.alloc(16, 8))
+    const [truncatedRecord] = anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from('nullifier16'),
+        nullifierRegistry.toBuffer(),
+        Buffer.from(truncatedHash),
+      ],
+      nullifierProgram.programId,
+    )
+
+    // A 16-byte form of a payment could otherwise be marked next to its 32-byte form
+    try {
+      await nullifierProgram.methods
+        .markNullifier(
+          { hash16: [truncatedHash] },
+          testClaimIdentifierHash,
+          false,
+          null,
+        )
+        .accountsStrict({
+          registry: nullifierRegistry,
+          callerAllowlist: null,
+          nullifierRecord: truncatedRecord,
+          stats,
+          rateLimit,
+          bloomFilter: null,
+          user: wallet.publicKey,
+          payer: wallet.publicKey,
+          callerAuthority: null,
+          callerProgram: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc()
+      assert.fail('Marking a 16-byte nullifier should fail')
+    } catch (error: any) {
+      assert.ok(
+        error.toString().includes('HashWidthMismatch'),
+        error.toString(),
+      )
+    }
+
+    try {
+      await nullifierProgram.methods
+        .checkNullifier({ hash16: [truncatedHash] }, false)
+        .accountsStrict({
+          registry: nullifierRegistry,
+          nullifierRecord: truncatedRecord,
+        })
+        .view()
+      assert.fail('Checking a 16-byte nullifier should fail')
+    } catch (error: any) {
+      assert.ok(
+        error.toString().includes('HashWidthMismatch'),
+        error.toString(),
+      )
+    }
   })
 
   it('Authorized wallet can mark another nullifier', async () => {
@@ -110,24 +415,40 @@ describe('nullifier-registry', () => {
     const testNullifierHash2 = Array.from(Buffer.alloc(32, 2)) // [2, 2, 2, 2, ..., 2] (32 bytes)
 
     const [nullifierRecord2] = anchor.web3.PublicKey.findProgramAddressSync(
-      [Buffer.from('nullifier'), Buffer.from(testNullifierHash2)],
+      [
+        Buffer.from('nullifier'),
+        nullifierRegistry.toBuffer(),
+        Buffer.from(testNullifierHash2),
+      ],
       nullifierProgram.programId,
     )
 
     try {
       const tx = await nullifierProgram.methods
-        .markNullifier(testNullifierHash2)
+        .markNullifier(
+          { hash32: [testNullifierHash2] },
+          testClaimIdentifierHash,
+          false,
+          null,
+        )
         .accountsStrict({
           registry: nullifierRegistry,
+          callerAllowlist: null,
           nullifierRecord: nullifierRecord2,
+          stats,
+          rateLimit,
+          bloomFilter: null,
           user: wallet.publicKey,
+          payer: wallet.publicKey,
+          callerAuthority: null,
+          callerProgram: null,
           systemProgram: SystemProgram.programId,
         })
         .rpc({
           skipPreflight: true,
         })
     } catch (error: any) {
-      if (error.message && error.message.includes('already in use')) {
+      if (error.message && error.message.includes('NullifierAlreadyUsed')) {
         console.log('✅ Nullifier already marked (from previous test)')
       } else {
         throw error
@@ -153,55 +474,1184 @@ describe('nullifier-registry', () => {
     )
   })
 
-  // it('Unauthorized user cannot mark nullifier', async () => {
-  //   console.log('\n=== Testing Unauthorized User ===')
-
-  //   const unauthorizedUser = Keypair.generate()
-
-  //   // Airdrop to unauthorized user for transaction fees
-  //   const airdropSig = await provider.connection.requestAirdrop(
-  //     unauthorizedUser.publicKey,
-  //     2 * anchor.web3.LAMPORTS_PER_SOL,
-  //   )
-  //   await provider.connection.confirmTransaction(airdropSig)
-
-  //   console.log('Unauthorized user:', unauthorizedUser.publicKey.toBase58())
-
-  //   // Create a different nullifier for unauthorized test
-  //   const unauthorizedNullifierHash = Array.from(Buffer.alloc(32, 2))
-  //   const [unauthorizedNullifierRecord] = anchor.web3.PublicKey.findProgramAddressSync(
-  //     [Buffer.from('nullifier'), Buffer.from(unauthorizedNullifierHash)],
-  //     nullifierProgram.programId,
-  //   )
-
-  //   try {
-  //     await nullifierProgram.methods
-  //       .markNullifier(unauthorizedNullifierHash)
-  //       .accountsStrict({
-  //         registry: nullifierRegistry,
-  //         nullifierRecord: unauthorizedNullifierRecord,
-  //         authority: unauthorizedUser.publicKey, // Wrong authority!
-  //         payer: unauthorizedUser.publicKey,
-  //         systemProgram: SystemProgram.programId,
-  //       })
-  //       .signers([unauthorizedUser])
-  //       .rpc({
-  //         skipPreflight: true,
-  //       })
-
-  //     assert.fail('Should have thrown UnauthorizedCaller error')
-  //   } catch (error: any) {
-  //     console.log('✅ Unauthorized user correctly rejected!')
-  //     const errorString = error.toString()
-  //     const isUnauthorizedError =
-  //       errorString.includes('UnauthorizedCaller') ||
-  //       errorString.includes('6003') || // Error code for UnauthorizedCaller
-  //       errorString.includes('A has_one constraint was violated')
-
-  //     assert.ok(isUnauthorizedError, 'Should fail with UnauthorizedCaller error')
-  //     console.log('Error:', errorString)
-  //   }
-
-  //   console.log('✅ Authority check working correctly!')
-  // })
+  it('Unauthorized user cannot mark nullifier', async () => {
+    console.log('\n=== Testing Unauthorized User ===')
+
+    const unauthorizedUser = Keypair.generate()
+
+    // Airdrop to unauthorized user for transaction fees
+    const airdropSig = await provider.connection.requestAirdrop(
+      unauthorizedUser.publicKey,
+      2 * anchor.web3.LAMPORTS_PER_SOL,
+    )
+    await provider.connection.confirmTransaction(airdropSig)
+
+    console.log('Unauthorized user:', unauthorizedUser.publicKey.toBase58())
+
+    // Create a different nullifier for unauthorized test
+    const unauthorizedNullifierHash = Array.from(Buffer.alloc(32, 3))
+    const [unauthorizedNullifierRecord] = anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from('nullifier'),
+        nullifierRegistry.toBuffer(),
+        Buffer.from(unauthorizedNullifierHash),
+      ],
+      nullifierProgram.programId,
+    )
+    const [unauthorizedRateLimit] = anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from('rate_limit'),
+        nullifierRegistry.toBuffer(),
+        unauthorizedUser.publicKey.toBuffer(),
+      ],
+      nullifierProgram.programId,
+    )
+
+    try {
+      await nullifierProgram.methods
+        .markNullifier(
+          { hash32: [unauthorizedNullifierHash] },
+          testClaimIdentifierHash,
+          false,
+          null,
+        )
+        .accountsStrict({
+          registry: nullifierRegistry,
+          callerAllowlist: null,
+          nullifierRecord: unauthorizedNullifierRecord,
+          stats,
+          rateLimit: unauthorizedRateLimit,
+          bloomFilter: null,
+          user: unauthorizedUser.publicKey,
+          payer: unauthorizedUser.publicKey,
+          callerAuthority: null,
+          callerProgram: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([unauthorizedUser])
+        .rpc()
+
+      assert.fail('Should have thrown UnauthorizedCaller error')
+    } catch (error: any) {
+      console.log('✅ Unauthorized user correctly rejected!')
+      const errorString = error.toString()
+      assert.ok(
+        errorString.includes('UnauthorizedCaller'),
+        'Should fail with UnauthorizedCaller error',
+      )
+    }
+  })
+
+  it('Rejects a caller authority that is not the caller program PDA', async () => {
+    const spoofedCaller = Keypair.generate()
+    const spoofedNullifierHash = Array.from(Buffer.alloc(32, 6))
+    const [spoofedNullifierRecord] =
+      anchor.web3.PublicKey.findProgramAddressSync(
+        [
+          Buffer.from('nullifier'),
+          nullifierRegistry.toBuffer(),
+          Buffer.from(spoofedNullifierHash),
+        ],
+        nullifierProgram.programId,
+      )
+
+    try {
+      await nullifierProgram.methods
+        .markNullifier(
+          { hash32: [spoofedNullifierHash] },
+          testClaimIdentifierHash,
+          false,
+          null,
+        )
+        .accountsStrict({
+          registry: nullifierRegistry,
+          callerAllowlist: null,
+          nullifierRecord: spoofedNullifierRecord,
+          stats,
+          rateLimit: null,
+          bloomFilter: null,
+          user: wallet.publicKey,
+          payer: wallet.publicKey,
+          callerAuthority: spoofedCaller.publicKey,
+          callerProgram: SystemProgram.programId,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([spoofedCaller])
+        .rpc()
+      assert.fail('A plain keypair should not pass as a caller PDA')
+    } catch (error: any) {
+      assert.ok(
+        error.toString().includes('InvalidCallerAuthority'),
+        `Unexpected error: ${error}`,
+      )
+    }
+  })
+
+  it('Marks and checks a batch of nullifiers', async () => {
+    console.log('\n=== Testing Batch Mark/Check ===')
+    const batchHashes = [4, 5].map((fill) => Array.from(Buffer.alloc(32, fill)))
+    const batchRecords = batchHashes.map(
+      (hash) =>
+        anchor.web3.PublicKey.findProgramAddressSync(
+          [
+            Buffer.from('nullifier'),
+            nullifierRegistry.toBuffer(),
+            Buffer.from(hash),
+          ],
+          nullifierProgram.programId,
+        )[0],
+    )
+    const remainingAccounts = batchRecords.map((pubkey) => ({
+      pubkey,
+      isWritable: true,
+      isSigner: false,
+    }))
+
+    try {
+      await nullifierProgram.methods
+        .markNullifiers(
+          batchHashes.map((nullifierHash) => ({
+            nullifierHash,
+            claimIdentifierHash: testClaimIdentifierHash,
+          })),
+        )
+        .accountsStrict({
+          registry: nullifierRegistry,
+          callerAllowlist: null,
+          stats,
+          rateLimit,
+          bloomFilter: null,
+          user: wallet.publicKey,
+          payer: wallet.publicKey,
+          callerAuthority: null,
+          callerProgram: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts(remainingAccounts)
+        .rpc()
+    } catch (error: any) {
+      if (error.toString().includes('NullifierAlreadyUsed')) {
+        console.log('✅ Batch already marked (from previous test)')
+      } else {
+        throw error
+      }
+    }
+
+    for (const [i, record] of batchRecords.entries()) {
+      const recordAccount =
+        await nullifierProgram.account.nullifierRecord.fetch(record)
+      assert.deepStrictEqual(recordAccount.nullifierHash, batchHashes[i])
+    }
+
+    try {
+      await nullifierProgram.methods
+        .checkNullifiers(batchHashes)
+        .accountsStrict({
+          registry: nullifierRegistry,
+        })
+        .remainingAccounts(remainingAccounts)
+        .rpc()
+      assert.fail('Should have thrown NullifierAlreadyUsed error')
+    } catch (error: any) {
+      assert.ok(
+        error.toString().includes('NullifierAlreadyUsed'),
+        'Should fail with NullifierAlreadyUsed error',
+      )
+    }
+  })
+
+  it('Reserves a nullifier and finalizes the reservation', async () => {
+    console.log('\n=== Testing Reservation ===')
+    const reservedHash = Array.from(Buffer.alloc(32, 6))
+    const [reservedRecord] = anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from('nullifier'),
+        nullifierRegistry.toBuffer(),
+        Buffer.from(reservedHash),
+      ],
+      nullifierProgram.programId,
+    )
+
+    try {
+      await nullifierProgram.methods
+        .reserveNullifier(reservedHash, new anchor.BN(600))
+        .accountsStrict({
+          registry: nullifierRegistry,
+          callerAllowlist: null,
+          nullifierRecord: reservedRecord,
+          rateLimit,
+          user: wallet.publicKey,
+          payer: wallet.publicKey,
+          callerAuthority: null,
+          callerProgram: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc()
+    } catch (error: any) {
+      if (error.toString().includes('NullifierAlreadyUsed')) {
+        console.log('✅ Reservation already finalized (from previous test)')
+        return
+      }
+      throw error
+    }
+
+    const reserved =
+      await nullifierProgram.account.nullifierRecord.fetch(reservedRecord)
+    assert.ok(reserved.reservedUntil, 'Record should be in reserved state')
+
+    const isUsed = await nullifierProgram.methods
+      .checkNullifier({ hash32: [reservedHash] }, false)
+      .accountsStrict({
+        registry: nullifierRegistry,
+        nullifierRecord: reservedRecord,
+      })
+      .view()
+    assert.strictEqual(isUsed, true, 'A live reservation should hold the nullifier')
+
+    await nullifierProgram.methods
+      .finalizeReservation(reservedHash, testClaimIdentifierHash)
+      .accountsStrict({
+        registry: nullifierRegistry,
+        callerAllowlist: null,
+        nullifierRecord: reservedRecord,
+        stats,
+        bloomFilter: null,
+        user: wallet.publicKey,
+        callerAuthority: null,
+        callerProgram: null,
+      })
+      .rpc()
+
+    const finalized =
+      await nullifierProgram.account.nullifierRecord.fetch(reservedRecord)
+    assert.strictEqual(finalized.reservedUntil, null, 'Reservation should be cleared')
+    assert.deepStrictEqual(finalized.claimIdentifierHash, testClaimIdentifierHash)
+  })
+
+  it('derive_and_mark hashes canonical fields on-chain', async () => {
+    console.log('\n=== Testing derive_and_mark ===')
+    const fields = ['609호이현민', '2025-06-17 22:08:30']
+    const derivedHash = calculateNullifier(fields.join(''))
+    const [derivedRecord] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from('nullifier'), nullifierRegistry.toBuffer(), derivedHash],
+      nullifierProgram.programId,
+    )
+
+    await nullifierProgram.methods
+      .deriveAndMark(fields, testClaimIdentifierHash, true)
+      .accountsStrict({
+        registry: nullifierRegistry,
+        callerAllowlist: null,
+        nullifierRecord: derivedRecord,
+        stats,
+        rateLimit,
+        bloomFilter: null,
+        user: wallet.publicKey,
+        payer: wallet.publicKey,
+        callerAuthority: null,
+        callerProgram: null,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc()
+
+    const recordAccount =
+      await nullifierProgram.account.nullifierRecord.fetch(derivedRecord)
+    assert.deepStrictEqual(
+      Buffer.from(recordAccount.nullifierHash),
+      derivedHash,
+      'On-chain derivation should match keccak256 of the concatenated fields',
+    )
+  })
+
+  it('Authority can add and remove allowlisted caller programs', async () => {
+    const [callerAllowlist] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from('caller_allowlist'), nullifierRegistry.toBuffer()],
+      nullifierProgram.programId,
+    )
+    const callerProgram = Keypair.generate().publicKey
+
+    await nullifierProgram.methods
+      .addAllowedCaller(callerProgram)
+      .accountsStrict({
+        registry: nullifierRegistry,
+        callerAllowlist,
+        authority: wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc()
+
+    let allowlist =
+      await nullifierProgram.account.callerAllowlist.fetch(callerAllowlist)
+    assert.ok(
+      allowlist.programs.some((program) => program.equals(callerProgram)),
+      'Program should be allowlisted',
+    )
+
+    await nullifierProgram.methods
+      .removeAllowedCaller(callerProgram)
+      .accountsStrict({
+        registry: nullifierRegistry,
+        callerAllowlist,
+        authority: wallet.publicKey,
+      })
+      .rpc()
+
+    allowlist =
+      await nullifierProgram.account.callerAllowlist.fetch(callerAllowlist)
+    assert.ok(
+      !allowlist.programs.some((program) => program.equals(callerProgram)),
+      'Program should be removed from the allowlist',
+    )
+  })
+
+  it('Expired records free their nullifier before they are closed', async () => {
+    const expiringHash = Array.from(Buffer.alloc(32, 10))
+    const [expiringRecord] = anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from('nullifier'),
+        nullifierRegistry.toBuffer(),
+        Buffer.from(expiringHash),
+      ],
+      nullifierProgram.programId,
+    )
+    const markAccounts = {
+      registry: nullifierRegistry,
+      callerAllowlist: null,
+      nullifierRecord: expiringRecord,
+      stats,
+      rateLimit,
+      bloomFilter: null,
+      user: wallet.publicKey,
+      payer: wallet.publicKey,
+      callerAuthority: null,
+      callerProgram: null,
+      systemProgram: SystemProgram.programId,
+    }
+    const setTtl = (ttl: number) =>
+      nullifierProgram.methods
+        .setNullifierTtl(new anchor.BN(ttl), wallet.publicKey)
+        .accountsStrict({
+          registry: nullifierRegistry,
+          authority: wallet.publicKey,
+        })
+        .rpc()
+    const isUsed = () =>
+      nullifierProgram.methods
+        .checkNullifier({ hash32: [expiringHash] }, false)
+        .accountsStrict({
+          registry: nullifierRegistry,
+          nullifierRecord: expiringRecord,
+        })
+        .view()
+
+    await setTtl(1)
+    try {
+      await nullifierProgram.methods
+        .markNullifier(
+          { hash32: [expiringHash] },
+          testClaimIdentifierHash,
+          true,
+          null,
+        )
+        .accountsStrict(markAccounts)
+        .rpc()
+
+      await new Promise((resolve) => setTimeout(resolve, 3000))
+      assert.strictEqual(
+        await isUsed(),
+        false,
+        'An expired record should not be reported as used',
+      )
+
+      // Non-idempotent re-mark succeeds and rewrites the record in place
+      await nullifierProgram.methods
+        .markNullifier(
+          { hash32: [expiringHash] },
+          testClaimIdentifierHash,
+          false,
+          null,
+        )
+        .accountsStrict(markAccounts)
+        .rpc()
+    } finally {
+      await setTtl(0)
+    }
+
+    const recordAccount =
+      await nullifierProgram.account.nullifierRecord.fetch(expiringRecord)
+    assert.ok(recordAccount.expiresAt, 'Re-marked record should carry a new expiry')
+  })
+
+  it('Bloom filter is created before any mark and updated by every mark', async () => {
+    const [mainBloomFilter] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from('bloom'), nullifierRegistry.toBuffer()],
+      nullifierProgram.programId,
+    )
+    try {
+      await nullifierProgram.methods
+        .initializeBloomFilter()
+        .accountsStrict({
+          registry: nullifierRegistry,
+          bloomFilter: mainBloomFilter,
+          authority: wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc()
+      assert.fail('A registry with marks should not get a bloom filter')
+    } catch (error: any) {
+      assert.ok(
+        error.toString().includes('BloomFilterAfterMarks'),
+        error.toString(),
+      )
+    }
+
+    // Fresh registry so the filter exists before the first mark
+    const {
+      registry: bloomRegistry,
+      stats: bloomStats,
+      rateLimit: bloomRateLimit,
+    } = await createFreshRegistry()
+    const [bloomFilter] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from('bloom'), bloomRegistry.toBuffer()],
+      nullifierProgram.programId,
+    )
+
+    await nullifierProgram.methods
+      .initializeBloomFilter()
+      .accountsStrict({
+        registry: bloomRegistry,
+        bloomFilter,
+        authority: wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc()
+
+    const bloomHash = Array.from(Buffer.alloc(32, 11))
+    const [bloomRecord] = anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from('nullifier'),
+        bloomRegistry.toBuffer(),
+        Buffer.from(bloomHash),
+      ],
+      nullifierProgram.programId,
+    )
+    const markAccounts = {
+      registry: bloomRegistry,
+      callerAllowlist: null,
+      nullifierRecord: bloomRecord,
+      stats: bloomStats,
+      rateLimit: bloomRateLimit,
+      bloomFilter,
+      user: wallet.publicKey,
+      payer: wallet.publicKey,
+      callerAuthority: null,
+      callerProgram: null,
+      systemProgram: SystemProgram.programId,
+    }
+
+    try {
+      await nullifierProgram.methods
+        .markNullifier(
+          { hash32: [bloomHash] },
+          testClaimIdentifierHash,
+          false,
+          null,
+        )
+        .accountsStrict({ ...markAccounts, bloomFilter: null })
+        .rpc()
+      assert.fail('Marking without the bloom filter should fail')
+    } catch (error: any) {
+      assert.ok(
+        error.toString().includes('BloomFilterRequired'),
+        error.toString(),
+      )
+    }
+
+    await nullifierProgram.methods
+      .markNullifier(
+        { hash32: [bloomHash] },
+        testClaimIdentifierHash,
+        false,
+        null,
+      )
+      .accountsStrict(markAccounts)
+      .rpc()
+
+    const mayBeUsed = await nullifierProgram.methods
+      .probeBloom(bloomHash)
+      .accountsStrict({ registry: bloomRegistry, bloomFilter })
+      .view()
+    assert.strictEqual(mayBeUsed, true, 'A marked nullifier must probe true')
+  })
+
+  it('Authority transfer needs the pending authority to accept', async () => {
+    const newAuthority = Keypair.generate()
+    const outsider = Keypair.generate()
+
+    await nullifierProgram.methods
+      .proposeAuthority(newAuthority.publicKey)
+      .accountsStrict({
+        registry: nullifierRegistry,
+        authority: wallet.publicKey,
+      })
+      .rpc()
+
+    let registryAccount =
+      await nullifierProgram.account.nullifierRegistry.fetch(nullifierRegistry)
+    assert.strictEqual(
+      registryAccount.authority.toBase58(),
+      wallet.publicKey.toBase58(),
+      'Proposing alone should not transfer control',
+    )
+    assert.strictEqual(
+      registryAccount.pendingAuthority?.toBase58(),
+      newAuthority.publicKey.toBase58(),
+    )
+
+    try {
+      await nullifierProgram.methods
+        .acceptAuthority()
+        .accountsStrict({
+          registry: nullifierRegistry,
+          newAuthority: outsider.publicKey,
+        })
+        .signers([outsider])
+        .rpc()
+      assert.fail('Only the pending authority may accept')
+    } catch (error: any) {
+      assert.ok(
+        error.toString().includes('NotPendingAuthority'),
+        error.toString(),
+      )
+    }
+
+    await nullifierProgram.methods
+      .acceptAuthority()
+      .accountsStrict({
+        registry: nullifierRegistry,
+        newAuthority: newAuthority.publicKey,
+      })
+      .signers([newAuthority])
+      .rpc()
+
+    try {
+      registryAccount =
+        await nullifierProgram.account.nullifierRegistry.fetch(nullifierRegistry)
+      assert.strictEqual(
+        registryAccount.authority.toBase58(),
+        newAuthority.publicKey.toBase58(),
+      )
+      assert.strictEqual(registryAccount.pendingAuthority, null)
+
+      try {
+        await nullifierProgram.methods
+          .setPaused(false)
+          .accountsStrict({
+            registry: nullifierRegistry,
+            authority: wallet.publicKey,
+          })
+          .rpc()
+        assert.fail('The previous authority should lose access')
+      } catch (error: any) {
+        assert.ok(
+          error.toString().includes('ConstraintHasOne'),
+          error.toString(),
+        )
+      }
+    } finally {
+      // Hand the registry back so the remaining tests keep using the wallet
+      await nullifierProgram.methods
+        .proposeAuthority(wallet.publicKey)
+        .accountsStrict({
+          registry: nullifierRegistry,
+          authority: newAuthority.publicKey,
+        })
+        .signers([newAuthority])
+        .rpc()
+      await nullifierProgram.methods
+        .acceptAuthority()
+        .accountsStrict({
+          registry: nullifierRegistry,
+          newAuthority: wallet.publicKey,
+        })
+        .rpc()
+    }
+  })
+
+  it('Pausing blocks every marking instruction until unpaused', async () => {
+    const randomHash = () => Array.from(Keypair.generate().publicKey.toBytes())
+    const recordFor = (hash: number[]) =>
+      anchor.web3.PublicKey.findProgramAddressSync(
+        [
+          Buffer.from('nullifier'),
+          nullifierRegistry.toBuffer(),
+          Buffer.from(hash),
+        ],
+        nullifierProgram.programId,
+      )[0]
+    const setPaused = (paused: boolean) =>
+      nullifierProgram.methods
+        .setPaused(paused)
+        .accountsStrict({
+          registry: nullifierRegistry,
+          authority: wallet.publicKey,
+        })
+        .rpc()
+
+    const markedHash = randomHash()
+    const batchHash = randomHash()
+    const reservedHash = randomHash()
+    const newReservationHash = randomHash()
+    const fields = ['paused', Keypair.generate().publicKey.toBase58()]
+    const derivedHash = calculateNullifier(fields.join(''))
+
+    const markAccounts = (hash: number[]) => ({
+      registry: nullifierRegistry,
+      callerAllowlist: null,
+      nullifierRecord: recordFor(hash),
+      stats,
+      rateLimit,
+      bloomFilter: null,
+      user: wallet.publicKey,
+      payer: wallet.publicKey,
+      callerAuthority: null,
+      callerProgram: null,
+      systemProgram: SystemProgram.programId,
+    })
+    const reserveAccounts = (hash: number[]) => ({
+      registry: nullifierRegistry,
+      callerAllowlist: null,
+      nullifierRecord: recordFor(hash),
+      rateLimit,
+      user: wallet.publicKey,
+      payer: wallet.publicKey,
+      callerAuthority: null,
+      callerProgram: null,
+      systemProgram: SystemProgram.programId,
+    })
+    const batchAccounts = {
+      registry: nullifierRegistry,
+      callerAllowlist: null,
+      stats,
+      rateLimit,
+      bloomFilter: null,
+      user: wallet.publicKey,
+      payer: wallet.publicKey,
+      callerAuthority: null,
+      callerProgram: null,
+      systemProgram: SystemProgram.programId,
+    }
+
+    const marking = {
+      markNullifier: () =>
+        nullifierProgram.methods
+          .markNullifier(
+            { hash32: [markedHash] },
+            testClaimIdentifierHash,
+            false,
+            null,
+          )
+          .accountsStrict(markAccounts(markedHash))
+          .rpc(),
+      deriveAndMark: () =>
+        nullifierProgram.methods
+          .deriveAndMark(fields, testClaimIdentifierHash, false)
+          .accountsStrict(markAccounts(Array.from(derivedHash)))
+          .rpc(),
+      markNullifiers: () =>
+        nullifierProgram.methods
+          .markNullifiers([
+            {
+              nullifierHash: batchHash,
+              claimIdentifierHash: testClaimIdentifierHash,
+            },
+          ])
+          .accountsStrict(batchAccounts)
+          .remainingAccounts([
+            { pubkey: recordFor(batchHash), isWritable: true, isSigner: false },
+          ])
+          .rpc(),
+      reserveNullifier: () =>
+        nullifierProgram.methods
+          .reserveNullifier(newReservationHash, new anchor.BN(600))
+          .accountsStrict(reserveAccounts(newReservationHash))
+          .rpc(),
+      finalizeReservation: () =>
+        nullifierProgram.methods
+          .finalizeReservation(reservedHash, testClaimIdentifierHash)
+          .accountsStrict({
+            registry: nullifierRegistry,
+            callerAllowlist: null,
+            nullifierRecord: recordFor(reservedHash),
+            stats,
+            bloomFilter: null,
+            user: wallet.publicKey,
+            callerAuthority: null,
+            callerProgram: null,
+          })
+          .rpc(),
+    }
+
+    // A reservation made before the pause, to be finalized
+    await nullifierProgram.methods
+      .reserveNullifier(reservedHash, new anchor.BN(600))
+      .accountsStrict(reserveAccounts(reservedHash))
+      .rpc()
+
+    await setPaused(true)
+    try {
+      for (const [name, instruction] of Object.entries(marking)) {
+        try {
+          await instruction()
+          assert.fail(`${name} should fail while paused`)
+        } catch (error: any) {
+          assert.ok(
+            error.toString().includes('RegistryPaused'),
+            `${name}: ${error.toString()}`,
+          )
+        }
+      }
+    } finally {
+      await setPaused(false)
+    }
+
+    for (const instruction of Object.values(marking)) {
+      await instruction()
+    }
+  })
+
+  it('Compressed registries append nullifiers with a low-leaf proof', async () => {
+    const hashNode = (left: Buffer, right: Buffer) =>
+      Buffer.from(
+        keccak256(Buffer.concat([Buffer.from([1]), left, right])).slice(2),
+        'hex',
+      )
+    // Siblings of leaf 0 while the tree holds only the sentinel (depth 20)
+    const emptyRoots = [Buffer.alloc(32)]
+    while (emptyRoots.length < 20) {
+      const below = emptyRoots[emptyRoots.length - 1]
+      emptyRoots.push(hashNode(below, below))
+    }
+
+    const { registry, stats: registryStats, rateLimit: registryRateLimit } =
+      await createFreshRegistry()
+    const [compressedSet] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from('compressed_set'), registry.toBuffer()],
+      nullifierProgram.programId,
+    )
+
+    await nullifierProgram.methods
+      .initializeCompressedSet()
+      .accountsStrict({
+        registry,
+        compressedSet,
+        authority: wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc()
+
+    const nullifierHash = Array.from(Keypair.generate().publicKey.toBytes())
+    const sentinelProof = {
+      lowLeaf: {
+        value: Array.from(Buffer.alloc(32)),
+        nextValue: Array.from(Buffer.alloc(32)),
+      },
+      lowIndex: new anchor.BN(0),
+      lowProof: emptyRoots.map((node) => Array.from(node)),
+    }
+    const appendAccounts = {
+      registry,
+      callerAllowlist: null,
+      compressedSet,
+      stats: registryStats,
+      bloomFilter: null,
+      user: wallet.publicKey,
+      callerAuthority: null,
+      callerProgram: null,
+    }
+
+    await nullifierProgram.methods
+      .verifyNonInclusion(nullifierHash, sentinelProof)
+      .accountsStrict({ registry, compressedSet })
+      .rpc()
+    await nullifierProgram.methods
+      .appendNullifier(nullifierHash, sentinelProof)
+      .accountsStrict(appendAccounts)
+      .rpc()
+
+    const set = await nullifierProgram.account.compressedNullifierSet.fetch(
+      compressedSet,
+    )
+    assert.strictEqual(set.leafCount.toNumber(), 2)
+
+    // The sentinel now points at the nullifier, so the old proof is stale
+    try {
+      await nullifierProgram.methods
+        .appendNullifier(nullifierHash, sentinelProof)
+        .accountsStrict(appendAccounts)
+        .rpc()
+      assert.fail('Re-appending with a stale proof should fail')
+    } catch (error: any) {
+      assert.ok(
+        error.toString().includes('InvalidMerkleProof'),
+        error.toString(),
+      )
+    }
+
+    // Record PDAs are not used for compressed registries
+    const [record] = anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from('nullifier'),
+        registry.toBuffer(),
+        Buffer.from(nullifierHash),
+      ],
+      nullifierProgram.programId,
+    )
+    try {
+      await nullifierProgram.methods
+        .markNullifier(
+          { hash32: [nullifierHash] },
+          testClaimIdentifierHash,
+          false,
+          null,
+        )
+        .accountsStrict({
+          registry,
+          callerAllowlist: null,
+          nullifierRecord: record,
+          stats: registryStats,
+          rateLimit: registryRateLimit,
+          bloomFilter: null,
+          user: wallet.publicKey,
+          payer: wallet.publicKey,
+          callerAuthority: null,
+          callerProgram: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc()
+      assert.fail('PDA marking should be rejected for compressed registries')
+    } catch (error: any) {
+      assert.ok(
+        error.toString().includes('CompressedRegistry'),
+        error.toString(),
+      )
+    }
+  })
+
+  it('Only the authority can revoke, and a revoked nullifier can be re-marked', async () => {
+    const revokedHash = Array.from(Keypair.generate().publicKey.toBytes())
+    const [revokedRecord] = anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from('nullifier'),
+        nullifierRegistry.toBuffer(),
+        Buffer.from(revokedHash),
+      ],
+      nullifierProgram.programId,
+    )
+    const registryAccount =
+      await nullifierProgram.account.nullifierRegistry.fetch(nullifierRegistry)
+    const markRevokedHash = () =>
+      nullifierProgram.methods
+        .markNullifier(
+          { hash32: [revokedHash] },
+          testClaimIdentifierHash,
+          false,
+          null,
+        )
+        .accountsStrict({
+          registry: nullifierRegistry,
+          callerAllowlist: null,
+          nullifierRecord: revokedRecord,
+          stats,
+          rateLimit,
+          bloomFilter: null,
+          user: wallet.publicKey,
+          payer: wallet.publicKey,
+          callerAuthority: null,
+          callerProgram: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc()
+    const revoke = (authority: Keypair | null) =>
+      nullifierProgram.methods
+        .revokeNullifier({ hash32: [revokedHash] }, 1)
+        .accountsStrict({
+          registry: nullifierRegistry,
+          nullifierRecord: revokedRecord,
+          rentRecipient: registryAccount.rentRecipient,
+          authority: authority ? authority.publicKey : wallet.publicKey,
+        })
+        .signers(authority ? [authority] : [])
+        .rpc()
+
+    await markRevokedHash()
+
+    try {
+      await revoke(Keypair.generate())
+      assert.fail('A non-authority should not be able to revoke')
+    } catch (error: any) {
+      assert.ok(
+        error.toString().includes('ConstraintHasOne'),
+        error.toString(),
+      )
+    }
+    assert.ok(
+      await provider.connection.getAccountInfo(revokedRecord),
+      'Record should survive the rejected revoke',
+    )
+
+    await revoke(null)
+    assert.strictEqual(
+      await provider.connection.getAccountInfo(revokedRecord),
+      null,
+      'Revoking should close the record',
+    )
+
+    // The nullifier is free again once its record is gone
+    await markRevokedHash()
+    const record =
+      await nullifierProgram.account.nullifierRecord.fetch(revokedRecord)
+    assert.ok(record.usedBy.equals(wallet.publicKey))
+  })
+
+  it('check_nullifier rejects spoofed registry and record accounts', async () => {
+    const unusedHash = Array.from(Keypair.generate().publicKey.toBytes())
+    const [unusedRecord] = anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from('nullifier'),
+        nullifierRegistry.toBuffer(),
+        Buffer.from(unusedHash),
+      ],
+      nullifierProgram.programId,
+    )
+    const check = (
+      registry: anchor.web3.PublicKey,
+      record: anchor.web3.PublicKey,
+    ) =>
+      nullifierProgram.methods
+        .checkNullifier({ hash32: [unusedHash] }, false)
+        .accountsStrict({ registry, nullifierRecord: record })
+        .view()
+    const expectError = async (
+      registry: anchor.web3.PublicKey,
+      record: anchor.web3.PublicKey,
+      expected: string,
+    ) => {
+      try {
+        await check(registry, record)
+        assert.fail(`Expected ${expected}`)
+      } catch (error: any) {
+        assert.ok(error.toString().includes(expected), error.toString())
+      }
+    }
+
+    // A lamport-funded system account at the record address is not a record
+    await provider.sendAndConfirm(
+      new anchor.web3.Transaction().add(
+        SystemProgram.transfer({
+          fromPubkey: wallet.publicKey,
+          toPubkey: unusedRecord,
+          lamports: anchor.web3.LAMPORTS_PER_SOL / 100,
+        }),
+      ),
+    )
+    assert.strictEqual(await check(nullifierRegistry, unusedRecord), false)
+
+    // The record of another hash (wrong seeds) cannot stand in for this one
+    await expectError(nullifierRegistry, nullifierRecord, 'ConstraintSeeds')
+
+    // The registry must be a registry account owned by this program
+    await expectError(
+      wallet.publicKey,
+      unusedRecord,
+      'AccountOwnedByWrongProgram',
+    )
+    await expectError(stats, unusedRecord, 'AccountDiscriminatorMismatch')
+  })
+
+  it('The rate-limit account is only required while a limit is set', async () => {
+    const markWithoutRateLimit = (hash: number[]) =>
+      nullifierProgram.methods
+        .markNullifier({ hash32: [hash] }, testClaimIdentifierHash, false, null)
+        .accountsStrict({
+          registry: nullifierRegistry,
+          callerAllowlist: null,
+          nullifierRecord: anchor.web3.PublicKey.findProgramAddressSync(
+            [
+              Buffer.from('nullifier'),
+              nullifierRegistry.toBuffer(),
+              Buffer.from(hash),
+            ],
+            nullifierProgram.programId,
+          )[0],
+          stats,
+          rateLimit: null,
+          bloomFilter: null,
+          user: wallet.publicKey,
+          payer: wallet.publicKey,
+          callerAuthority: null,
+          callerProgram: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc()
+    const setRateLimit = (maxMarksPerHour: number) =>
+      nullifierProgram.methods
+        .setRateLimit(maxMarksPerHour)
+        .accountsStrict({
+          registry: nullifierRegistry,
+          authority: wallet.publicKey,
+        })
+        .rpc()
+
+    // No limit: marking needs no per-user account
+    await markWithoutRateLimit(
+      Array.from(Keypair.generate().publicKey.toBytes()),
+    )
+
+    await setRateLimit(100)
+    try {
+      await markWithoutRateLimit(
+        Array.from(Keypair.generate().publicKey.toBytes()),
+      )
+      assert.fail('Marking without the rate-limit account should fail')
+    } catch (error: any) {
+      assert.ok(
+        error.toString().includes('RateLimitAccountRequired'),
+        error.toString(),
+      )
+    } finally {
+      await setRateLimit(0)
+    }
+
+    // Without a limit the counter can be closed and its rent reclaimed
+    await nullifierProgram.methods
+      .closeRateLimit()
+      .accountsStrict({
+        registry: nullifierRegistry,
+        rateLimit,
+        user: wallet.publicKey,
+      })
+      .rpc()
+    assert.strictEqual(
+      await provider.connection.getAccountInfo(rateLimit),
+      null,
+    )
+  })
+
+  it('import_batch checks record addresses and keeps the marking program', async () => {
+    const { registry, stats: registryStats } = await createFreshRegistry()
+    const source = await nullifierProgram.account.nullifierRecord.fetch(
+      nullifierRecord,
+    )
+    const entry = {
+      nullifier: { hash32: [testNullifierHash] },
+      usedAt: source.usedAt,
+      usedBy: source.usedBy,
+      claimIdentifierHash: source.claimIdentifierHash,
+      markedByProgram: source.markedByProgram,
+    }
+    const [importedRecord] = anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from('nullifier'),
+        registry.toBuffer(),
+        Buffer.from(testNullifierHash),
+      ],
+      nullifierProgram.programId,
+    )
+    const importInto = (record: anchor.web3.PublicKey) =>
+      nullifierProgram.methods
+        .importBatch([entry])
+        .accountsStrict({
+          registry,
+          stats: registryStats,
+          bloomFilter: null,
+          authority: wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts([
+          { pubkey: record, isWritable: true, isSigner: false },
+        ])
+        .rpc()
+
+    // The source registry's record is not this registry's PDA for the entry
+    try {
+      await importInto(nullifierRecord)
+      assert.fail('Importing into a foreign record should fail')
+    } catch (error: any) {
+      assert.ok(
+        error.toString().includes('InvalidNullifierAccount'),
+        error.toString(),
+      )
+    }
+
+    await importInto(importedRecord)
+    const imported =
+      await nullifierProgram.account.nullifierRecord.fetch(importedRecord)
+    assert.ok(imported.markedByProgram.equals(source.markedByProgram))
+    assert.ok(imported.usedBy.equals(source.usedBy))
+  })
+
+  it('Signers in the caller allowlist may mark directly', async () => {
+    const [callerAllowlist] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from('caller_allowlist'), nullifierRegistry.toBuffer()],
+      nullifierProgram.programId,
+    )
+    const relayer = Keypair.generate()
+    const markAsRelayer = () => {
+      const hash = Array.from(Keypair.generate().publicKey.toBytes())
+      return nullifierProgram.methods
+        .markNullifier({ hash32: [hash] }, testClaimIdentifierHash, false, null)
+        .accountsStrict({
+          registry: nullifierRegistry,
+          callerAllowlist,
+          nullifierRecord: anchor.web3.PublicKey.findProgramAddressSync(
+            [
+              Buffer.from('nullifier'),
+              nullifierRegistry.toBuffer(),
+              Buffer.from(hash),
+            ],
+            nullifierProgram.programId,
+          )[0],
+          stats,
+          rateLimit: null,
+          bloomFilter: null,
+          user: relayer.publicKey,
+          payer: wallet.publicKey,
+          callerAuthority: null,
+          callerProgram: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([relayer])
+        .rpc()
+    }
+
+    await nullifierProgram.methods
+      .addAllowedCaller(relayer.publicKey)
+      .accountsStrict({
+        registry: nullifierRegistry,
+        callerAllowlist,
+        authority: wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc()
+    await markAsRelayer()
+
+    await nullifierProgram.methods
+      .removeAllowedCaller(relayer.publicKey)
+      .accountsStrict({
+        registry: nullifierRegistry,
+        callerAllowlist,
+        authority: wallet.publicKey,
+      })
+      .rpc()
+    try {
+      await markAsRelayer()
+      assert.fail('A removed signer should no longer be able to mark')
+    } catch (error: any) {
+      assert.ok(
+        error.toString().includes('UnauthorizedCaller'),
+        error.toString(),
+      )
+    }
+  })
 })
@@ -1,6 +1,11 @@
 import { expect } from 'chai'
 import * as anchor from '@coral-xyz/anchor'
-import { loadProof, getProgram, serializeSignature } from './utils'
+import {
+  loadProof,
+  getProgram,
+  serializeSignature,
+  calculateNullifier,
+} from './utils'
 
 describe('verify_proof_signatures', () => {
   const program = getProgram()
@@ -17,6 +22,7 @@ describe('verify_proof_signatures', () => {
       claim: {
         identifier: fixture.signedClaim.claim.identifier,
         owner: fixture.signedClaim.claim.owner,
+        ownerFormat: { ethereum: {} },
         timestampS: fixture.signedClaim.claim.timestampS,
         epoch: fixture.signedClaim.claim.epoch,
       },
@@ -29,6 +35,8 @@ describe('verify_proof_signatures', () => {
   const payer = provider.wallet as anchor.Wallet
 
   let paymentConfigPda: anchor.web3.PublicKey
+  let witnessRegistryPda: anchor.web3.PublicKey
+  let verificationResultPda: anchor.web3.PublicKey
 
   before(async () => {
     // Find payment config PDA
@@ -57,6 +65,31 @@ describe('verify_proof_signatures', () => {
         throw e
       }
     }
+
+    // verify_proof only counts witnesses from the shared registry
+    ;[witnessRegistryPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from('witness_registry')],
+      program.programId,
+    )
+    ;[verificationResultPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from('verification'), payer.publicKey.toBuffer()],
+      program.programId,
+    )
+    try {
+      await program.methods
+        .initializeWitnessRegistry([fixture.expectedWitness], 1)
+        .accounts({
+          authority: payer.publicKey,
+        })
+        .rpc()
+      console.log('✅ Witness registry initialized')
+    } catch (e: any) {
+      if (e.message && e.message.includes('already in use')) {
+        console.log('✅ Witness registry already initialized')
+      } else {
+        throw e
+      }
+    }
   })
 
   it('verifies only proof signatures (VerifyProofOnly)', async () => {
@@ -152,6 +185,7 @@ describe('verify_proof_signatures', () => {
         claim: {
           identifier: 'test1_' + fixture.signedClaim.claim.identifier, // Unique identifier for this test
           owner: fixture.signedClaim.claim.owner,
+          ownerFormat: { ethereum: {} },
           timestampS: fixture.signedClaim.claim.timestampS,
           epoch: fixture.signedClaim.claim.epoch,
         },
@@ -181,4 +215,85 @@ describe('verify_proof_signatures', () => {
       expect(error.error.errorCode.code).to.equal('InvalidThreshold')
     }
   })
+
+  it('verifies a proof against the witness registry (VerifyWithRegistry)', async () => {
+    await program.methods
+      .verifyWithRegistry(baseProof)
+      .accountsStrict({
+        witnessRegistry: witnessRegistryPda,
+      })
+      .rpc()
+  })
+
+  it('rejects expected witnesses that are not in the witness registry', async () => {
+    const unknownWitness = '0x' + '11'.repeat(20)
+
+    try {
+      await program.methods
+        .verifyProof(baseProof, [fixture.expectedWitness, unknownWitness], 1)
+        .accounts({
+          signer: payer.publicKey,
+        })
+        .rpc()
+
+      throw new Error('Expected transaction to fail but it succeeded')
+    } catch (error: any) {
+      expect(error.error.errorCode.code).to.equal('UnknownWitness')
+    }
+  })
+
+  it('records the threshold tier reached by the proof', async () => {
+    await program.methods
+      .setThresholdTiers(
+        [
+          { name: 'mint', threshold: 1 },
+          { name: 'withdraw', threshold: 2 },
+        ],
+        1,
+      )
+      .accounts({
+        authority: payer.publicKey,
+      })
+      .rpc()
+
+    try {
+      await program.methods
+        .verifyProof(baseProof, [fixture.expectedWitness], 1)
+        .accounts({
+          signer: payer.publicKey,
+        })
+        .rpc()
+
+      const result =
+        await program.account.verificationResult.fetch(verificationResultPda)
+      expect(result.validWitnessCount).to.equal(1)
+      expect(result.tier).to.equal(1)
+    } finally {
+      await program.methods
+        .setThresholdTiers([], 0)
+        .accounts({
+          authority: payer.publicKey,
+        })
+        .rpc()
+    }
+  })
+
+  it('stores the payment nullifier derived from the proof context', async () => {
+    await program.methods
+      .verifyProof(baseProof, [fixture.expectedWitness], 1)
+      .accounts({
+        signer: payer.publicKey,
+      })
+      .rpc()
+
+    // Same fields and order as nullifier-derive's DEFAULT_NULLIFIER_FIELDS
+    const { senderNickname, transactionDate } = JSON.parse(
+      fixture.claimInfo.context,
+    ).extractedParameters
+    const result =
+      await program.account.verificationResult.fetch(verificationResultPda)
+    expect(Buffer.from(result.nullifier)).to.deep.equal(
+      calculateNullifier(senderNickname + transactionDate),
+    )
+  })
 })